@@ -0,0 +1,121 @@
+//! CRC throughput: every safe (dispatched) checksum, plus the `_base` and,
+//! where the CPU has CLMUL, the `_by8` kernels behind them.
+
+use std::os::raw::{c_int, c_uchar};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use isa_l_rust::crc;
+use isa_l_rust::sys;
+
+const SIZES: [usize; 3] = [4 << 10, 64 << 10, 1 << 20];
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 + (i >> 8)) as u8).collect()
+}
+
+type Crc64Fn = unsafe extern "C" fn(u64, *const c_uchar, u64) -> u64;
+
+macro_rules! crc64_kernels {
+    ($($name:ident: $base:ident, $by8:ident;)*) => {
+        [$(
+            (
+                stringify!($name),
+                crc::$name as fn(u64, &[u8]) -> u64,
+                sys::$base as Crc64Fn,
+                sys::$by8 as Crc64Fn,
+            ),
+        )*]
+    };
+}
+
+fn crc16_32(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crc");
+    for len in SIZES {
+        let buf = data(len);
+        let mut dst = vec![0u8; len];
+        group.throughput(Throughput::Bytes(len as u64));
+
+        group.bench_with_input(BenchmarkId::new("crc16_t10dif", len), &buf, |b, buf| {
+            b.iter(|| crc::crc16_t10dif(0, buf))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("crc16_t10dif_base", len),
+            &buf,
+            |b, buf| {
+                b.iter(|| unsafe { sys::crc16_t10dif_base(0, buf.as_ptr() as *mut u8, len as u64) })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("crc16_t10dif_copy", len),
+            &buf,
+            |b, buf| b.iter(|| crc::crc16_t10dif_copy(0, &mut dst, buf)),
+        );
+        group.bench_with_input(BenchmarkId::new("crc32_ieee", len), &buf, |b, buf| {
+            b.iter(|| crc::crc32_ieee(0, buf))
+        });
+        group.bench_with_input(BenchmarkId::new("crc32_ieee_base", len), &buf, |b, buf| {
+            b.iter(|| unsafe { sys::crc32_ieee_base(0, buf.as_ptr() as *mut u8, len as u64) })
+        });
+        group.bench_with_input(BenchmarkId::new("crc32_gzip_refl", len), &buf, |b, buf| {
+            b.iter(|| crc::crc32_gzip_refl(0, buf))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("crc32_gzip_refl_base", len),
+            &buf,
+            |b, buf| {
+                b.iter(|| unsafe {
+                    sys::crc32_gzip_refl_base(0, buf.as_ptr() as *mut u8, len as u64)
+                })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("crc32_iscsi", len), &buf, |b, buf| {
+            b.iter(|| crc::crc32_iscsi(0, buf))
+        });
+        group.bench_with_input(BenchmarkId::new("crc32_iscsi_base", len), &buf, |b, buf| {
+            b.iter(|| unsafe { sys::crc32_iscsi_base(buf.as_ptr() as *mut u8, len as c_int, 0) })
+        });
+    }
+    group.finish();
+}
+
+fn crc64(c: &mut Criterion) {
+    let kernels = crc64_kernels! {
+        crc64_ecma_refl: crc64_ecma_refl_base, crc64_ecma_refl_by8;
+        crc64_ecma_norm: crc64_ecma_norm_base, crc64_ecma_norm_by8;
+        crc64_iso_refl: crc64_iso_refl_base, crc64_iso_refl_by8;
+        crc64_iso_norm: crc64_iso_norm_base, crc64_iso_norm_by8;
+        crc64_jones_refl: crc64_jones_refl_base, crc64_jones_refl_by8;
+        crc64_jones_norm: crc64_jones_norm_base, crc64_jones_norm_by8;
+        crc64_rocksoft_refl: crc64_rocksoft_refl_base, crc64_rocksoft_refl_by8;
+        crc64_rocksoft_norm: crc64_rocksoft_norm_base, crc64_rocksoft_norm_by8;
+    };
+    let cpu = isa_l_rust::cpu::cpu_features();
+    let by8 = cfg!(target_arch = "x86_64") && cpu.clmul && cpu.sse4_1;
+
+    let mut group = c.benchmark_group("crc64");
+    for len in SIZES {
+        let buf = data(len);
+        group.throughput(Throughput::Bytes(len as u64));
+        for (name, safe, base, fast) in kernels {
+            group.bench_with_input(BenchmarkId::new(name, len), &buf, |b, buf| {
+                b.iter(|| safe(0, buf))
+            });
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}_base"), len),
+                &buf,
+                |b, buf| b.iter(|| unsafe { base(0, buf.as_ptr(), len as u64) }),
+            );
+            if by8 {
+                group.bench_with_input(
+                    BenchmarkId::new(format!("{name}_by8"), len),
+                    &buf,
+                    |b, buf| b.iter(|| unsafe { fast(0, buf.as_ptr(), len as u64) }),
+                );
+            }
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, crc16_32, crc64);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+//! Erasure-coding throughput for common `k + p` layouts, through the
+//! dispatcher and through every backend the CPU supports.
+//!
+//! Throughput counts the data bytes (`k * shard`) processed per call.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use isa_l_rust::cpu::Backend;
+use isa_l_rust::erasure::ErasureCoder;
+
+const LAYOUTS: [(usize, usize); 3] = [(4, 2), (8, 3), (10, 4)];
+const SHARD_LEN: usize = 64 << 10;
+
+fn coders(k: usize, p: usize) -> Vec<(String, ErasureCoder)> {
+    let mut coders = vec![("dispatched".to_string(), ErasureCoder::new(k, p).unwrap())];
+    for backend in Backend::ALL {
+        if let Ok(coder) = ErasureCoder::with_backend(k, p, backend) {
+            coders.push((backend.name().to_string(), coder));
+        }
+    }
+    coders
+}
+
+fn shards(k: usize, p: usize) -> Vec<Vec<u8>> {
+    (0..k + p)
+        .map(|s| (0..SHARD_LEN).map(|i| (i * 7 + s * 131) as u8).collect())
+        .collect()
+}
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ec_encode");
+    for (k, p) in LAYOUTS {
+        group.throughput(Throughput::Bytes((k * SHARD_LEN) as u64));
+        let mut all = shards(k, p);
+        let (data, parity) = all.split_at_mut(k);
+        let data: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+        let mut parity: Vec<&mut [u8]> = parity.iter_mut().map(|d| &mut d[..]).collect();
+
+        for (name, coder) in coders(k, p) {
+            group.bench_function(BenchmarkId::new(name, format!("{k}+{p}")), |b| {
+                b.iter(|| coder.encode(&data, &mut parity).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ec_decode");
+    for (k, p) in LAYOUTS {
+        group.throughput(Throughput::Bytes((k * SHARD_LEN) as u64));
+        // Lose as many data shards as the parity allows: the worst case.
+        let erasures: Vec<usize> = (0..p).collect();
+
+        for (name, coder) in coders(k, p) {
+            let mut all = shards(k, p);
+            {
+                let (data, parity) = all.split_at_mut(k);
+                let data: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+                let mut parity: Vec<&mut [u8]> = parity.iter_mut().map(|d| &mut d[..]).collect();
+                coder.encode(&data, &mut parity).unwrap();
+            }
+            let mut refs: Vec<&mut [u8]> = all.iter_mut().map(|s| &mut s[..]).collect();
+            group.bench_function(BenchmarkId::new(name, format!("{k}+{p}")), |b| {
+                b.iter(|| coder.reconstruct(&mut refs, &erasures).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, encode, decode);
+criterion_main!(benches);
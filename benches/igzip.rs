@@ -0,0 +1,134 @@
+//! igzip throughput at levels 0-3, compressing and decompressing a set of
+//! corpora. Throughput counts uncompressed bytes in both directions.
+//!
+//! The built-in corpora are synthetic (English-like text, an XML-like log,
+//! incompressible bytes and zeros). To measure standard corpora such as
+//! Canterbury or Silesia, point `ISAL_BENCH_CORPUS` at a directory; every
+//! regular file in it is added as its own input.
+
+use std::env;
+use std::fs;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use isa_l_rust::igzip::{Compressor, Decompressor};
+
+const LEVELS: [u32; 4] = [0, 1, 2, 3];
+const CORPUS_LEN: usize = 4 << 20;
+
+const WORDS: [&str; 16] = [
+    "the", "of", "and", "storage", "a", "to", "in", "erasure", "is", "that", "for", "parity", "it",
+    "with", "as", "checksum",
+];
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn text(len: usize) -> Vec<u8> {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut out = Vec::with_capacity(len + 16);
+    while out.len() < len {
+        out.extend_from_slice(WORDS[(rng.next() % 16) as usize].as_bytes());
+        out.push(if rng.next() & 15 == 0 { b'\n' } else { b' ' });
+    }
+    out.truncate(len);
+    out
+}
+
+fn log(len: usize) -> Vec<u8> {
+    let mut rng = Rng(42);
+    let mut out = Vec::with_capacity(len + 128);
+    let mut seq = 0u64;
+    while out.len() < len {
+        let line = format!(
+            "<event seq=\"{seq}\" shard=\"{}\" bytes=\"{}\" status=\"{}\"/>\n",
+            rng.next() % 14,
+            rng.next() % 65536,
+            if rng.next() & 63 == 0 {
+                "degraded"
+            } else {
+                "ok"
+            },
+        );
+        out.extend_from_slice(line.as_bytes());
+        seq += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+fn random(len: usize) -> Vec<u8> {
+    let mut rng = Rng(7);
+    (0..len).map(|_| rng.next() as u8).collect()
+}
+
+fn corpora() -> Vec<(String, Vec<u8>)> {
+    let mut corpora = vec![
+        ("text".to_string(), text(CORPUS_LEN)),
+        ("log".to_string(), log(CORPUS_LEN)),
+        ("random".to_string(), random(CORPUS_LEN)),
+        ("zeros".to_string(), vec![0u8; CORPUS_LEN]),
+    ];
+    if let Some(dir) = env::var_os("ISAL_BENCH_CORPUS") {
+        let mut entries: Vec<_> = fs::read_dir(&dir)
+            .expect("ISAL_BENCH_CORPUS is not a readable directory")
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+            .collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let data = fs::read(entry.path()).expect("failed to read corpus file");
+            corpora.push((entry.file_name().to_string_lossy().into_owned(), data));
+        }
+    }
+    corpora
+}
+
+fn compress(c: &mut Criterion) {
+    let corpora = corpora();
+    let mut group = c.benchmark_group("deflate");
+    for (name, data) in &corpora {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        for level in LEVELS {
+            let mut compressor = Compressor::new(level).unwrap();
+            let mut out = Vec::with_capacity(data.len() + (64 << 10));
+            group.bench_function(BenchmarkId::new(format!("level{level}"), name), |b| {
+                b.iter(|| {
+                    out.clear();
+                    compressor.compress_into(data, &mut out).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn decompress(c: &mut Criterion) {
+    let corpora = corpora();
+    let mut group = c.benchmark_group("inflate");
+    for (name, data) in &corpora {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        for level in LEVELS {
+            let compressed = Compressor::new(level).unwrap().compress(data).unwrap();
+            let mut decompressor = Decompressor::new();
+            let mut out = Vec::with_capacity(data.len());
+            group.bench_function(BenchmarkId::new(format!("level{level}"), name), |b| {
+                b.iter(|| {
+                    out.clear();
+                    decompressor.decompress_into(&compressed, &mut out).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compress, decompress);
+criterion_main!(benches);
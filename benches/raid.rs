@@ -0,0 +1,123 @@
+//! RAID5/RAID6 parity throughput: the safe `xor_gen` / `pq_gen` (dispatched
+//! kernels on aligned buffers), `_base`, and every arch-specific variant the
+//! CPU supports.
+//!
+//! Throughput counts the source bytes read per call.
+
+use std::os::raw::{c_int, c_void};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use isa_l_rust::cpu::Backend;
+use isa_l_rust::raid;
+use isa_l_rust::sys;
+
+const SOURCES: [usize; 2] = [4, 10];
+const VECTOR_LEN: usize = 64 << 10;
+/// Alignment accepted by every SIMD RAID kernel.
+const ALIGN: usize = 64;
+
+type RaidFn = unsafe extern "C" fn(c_int, c_int, *mut *mut c_void) -> c_int;
+
+/// `count` vectors of `VECTOR_LEN` bytes carved from one allocation, each
+/// aligned to `ALIGN`.
+struct Vectors {
+    arena: Vec<u8>,
+    offset: usize,
+    count: usize,
+}
+
+impl Vectors {
+    fn new(count: usize) -> Self {
+        let mut arena: Vec<u8> = (0..count * VECTOR_LEN + ALIGN)
+            .map(|i| (i * 29 + 3) as u8)
+            .collect();
+        let offset = arena.as_mut_ptr().align_offset(ALIGN);
+        Vectors {
+            arena,
+            offset,
+            count,
+        }
+    }
+
+    fn split(&mut self, sources: usize) -> (Vec<&[u8]>, Vec<&mut [u8]>) {
+        let body = &mut self.arena[self.offset..self.offset + self.count * VECTOR_LEN];
+        let (src, dst) = body.split_at_mut(sources * VECTOR_LEN);
+        (
+            src.chunks(VECTOR_LEN).collect(),
+            dst.chunks_mut(VECTOR_LEN).collect(),
+        )
+    }
+
+    fn pointers(&mut self) -> Vec<*mut c_void> {
+        let base = self.arena[self.offset..].as_mut_ptr();
+        (0..self.count)
+            .map(|i| unsafe { base.add(i * VECTOR_LEN) } as *mut c_void)
+            .collect()
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn variants(name: &str) -> Vec<(Backend, RaidFn, &'static str)> {
+    match name {
+        "xor_gen" => vec![
+            (Backend::Sse, sys::xor_gen_sse as RaidFn, "sse"),
+            (Backend::Avx, sys::xor_gen_avx, "avx"),
+            (Backend::Avx512, sys::xor_gen_avx512, "avx512"),
+        ],
+        _ => vec![
+            (Backend::Sse, sys::pq_gen_sse as RaidFn, "sse"),
+            (Backend::Avx, sys::pq_gen_avx, "avx"),
+            (Backend::Avx2, sys::pq_gen_avx2, "avx2"),
+            (Backend::Avx512, sys::pq_gen_avx512, "avx512"),
+        ],
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn variants(_name: &str) -> Vec<(Backend, RaidFn, &'static str)> {
+    Vec::new()
+}
+
+fn bench_kernels(c: &mut Criterion, name: &str, parity: usize, base: RaidFn) {
+    let mut group = c.benchmark_group(name);
+    for sources in SOURCES {
+        group.throughput(Throughput::Bytes((sources * VECTOR_LEN) as u64));
+        let mut vectors = Vectors::new(sources + parity);
+
+        {
+            let (src, mut dst) = vectors.split(sources);
+            group.bench_function(BenchmarkId::new("dispatched", sources), |b| {
+                b.iter(|| match &mut dst[..] {
+                    [p] => raid::xor_gen(&src, p).unwrap(),
+                    [p, q] => raid::pq_gen(&src, p, q).unwrap(),
+                    _ => unreachable!(),
+                })
+            });
+        }
+
+        let mut array = vectors.pointers();
+        let vects = array.len() as c_int;
+        let mut kernels = vec![(Backend::Base, base, "base")];
+        kernels.extend(variants(name));
+        for (backend, f, suffix) in kernels {
+            if !backend.is_supported() {
+                continue;
+            }
+            group.bench_function(BenchmarkId::new(suffix, sources), |b| {
+                b.iter(|| unsafe { f(vects, VECTOR_LEN as c_int, array.as_mut_ptr()) })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn xor(c: &mut Criterion) {
+    bench_kernels(c, "xor_gen", 1, sys::xor_gen_base);
+}
+
+fn pq(c: &mut Criterion) {
+    bench_kernels(c, "pq_gen", 2, sys::pq_gen_base);
+}
+
+criterion_group!(benches, xor, pq);
+criterion_main!(benches);
@@ -0,0 +1,240 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Oldest ISA-L release whose API matches the bindings in src/lib.rs.
+// Keep in sync with ISAL_MAJOR_VERSION / ISAL_MINOR_VERSION there.
+const ISAL_MAJOR_VERSION: u32 = 2;
+const ISAL_MINOR_VERSION: u32 = 31;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ISAL_SYS_USE_PKG_CONFIG");
+    println!("cargo:rerun-if-env-changed=ISAL_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=ISAL_INCLUDE_DIR");
+    println!("cargo:rerun-if-env-changed=ISAL_STATIC");
+
+    let use_system = env::var_os("CARGO_FEATURE_SYSTEM").is_some()
+        || env::var_os("ISAL_SYS_USE_PKG_CONFIG").is_some_and(|v| !v.is_empty() && v != "0");
+
+    let include_dirs = if let Some(lib_dir) = env::var_os("ISAL_LIB_DIR") {
+        link_lib_dir(Path::new(&lib_dir))
+    } else if use_system {
+        link_pkg_config()
+    } else {
+        build_bundled()
+    };
+
+    if env::var_os("CARGO_FEATURE_CRYPTO").is_some() {
+        let crypto_include_dirs = if use_system {
+            link_crypto_pkg_config()
+        } else {
+            build_crypto_bundled()
+        };
+
+        let mut helpers = cc::Build::new();
+        helpers.file("src/crypto_helpers.c");
+        for dir in &crypto_include_dirs {
+            helpers.include(dir);
+            helpers.include(dir.join("isa-l_crypto"));
+        }
+        helpers.compile("isal_rs_crypto_helpers");
+        println!("cargo:rerun-if-changed=src/crypto_helpers.c");
+    }
+
+    // Compile the sizeof/field-accessor shim for the opaque igzip structs
+    let mut helpers = cc::Build::new();
+    helpers.file("src/helpers.c");
+    for dir in &include_dirs {
+        helpers.include(dir);
+        // Distro packages install the headers under <includedir>/isa-l/
+        helpers.include(dir.join("isa-l"));
+    }
+    helpers.compile("isal_rs_helpers");
+    println!("cargo:rerun-if-changed=src/helpers.c");
+
+    // Exposed to dependents as DEP_ISAL_INCLUDE
+    if let Some(dir) = include_dirs.first() {
+        println!("cargo:include={}", dir.display());
+    }
+
+    #[cfg(feature = "bindgen")]
+    generate_bindings(&include_dirs);
+}
+
+/// Generates `$OUT_DIR/bindings.rs` from the isa-l headers so the test suite
+/// can diff it against the hand-written declarations.
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_dirs: &[PathBuf]) {
+    let mut builder = bindgen::Builder::default()
+        .header("src/wrapper.h")
+        .allowlist_function("(crc|ec_|gf_|isal_|xor_|pq_).*")
+        .opaque_type("isal_.*|inflate_state")
+        .layout_tests(false)
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()));
+    for dir in include_dirs {
+        builder = builder
+            .clang_arg(format!("-I{}", dir.display()))
+            .clang_arg(format!("-I{}", dir.join("isa-l").display()));
+    }
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("bindings.rs");
+    builder
+        .generate()
+        .expect("bindgen failed to parse the isa-l headers")
+        .write_to_file(out)
+        .expect("failed to write bindings.rs");
+}
+
+/// Builds the isa-l submodule with CMake and links it statically.
+fn build_bundled() -> Vec<PathBuf> {
+    let mut cfg = cmake::Config::new("isa-l");
+
+    cfg.define("BUILD_SHARED_LIBS", "OFF")
+        .define("ISAL_BUILD_TESTS", "OFF")
+        .define("BUILD_FUZZ_TESTS", "OFF")
+        .define("BUILD_ISAL_SHIM", "OFF");
+
+    let dst = cfg.build();
+
+    // Link the static library
+    println!("cargo:rustc-link-search=native={}/lib", dst.display());
+    println!("cargo:rustc-link-lib=static=isal");
+
+    // Re-run if isa-l source changes
+    println!("cargo:rerun-if-changed=isa-l/");
+
+    vec![PathBuf::from("isa-l/include")]
+}
+
+/// Builds the isa-l_crypto submodule with its Makefile and links it statically.
+///
+/// The sources are copied into `OUT_DIR` first so the build does not write
+/// into the submodule checkout.
+fn build_crypto_bundled() -> Vec<PathBuf> {
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    let src = out_dir.join("isa-l_crypto");
+    copy_dir(Path::new("isa-l_crypto"), &src);
+
+    let jobs = env::var("NUM_JOBS").unwrap_or_else(|_| "1".to_string());
+    let status = Command::new("make")
+        .args(["-f", "Makefile.unx", "lib", "-j", &jobs])
+        .current_dir(&src)
+        .status()
+        .expect("failed to run make for isa-l_crypto");
+    assert!(status.success(), "isa-l_crypto build failed");
+
+    // Makefile.unx names the archive without the `lib` prefix rustc expects
+    let lib_dir = out_dir.join("lib");
+    fs::create_dir_all(&lib_dir).unwrap();
+    fs::copy(
+        src.join("bin/isa-l_crypto.a"),
+        lib_dir.join("libisal_crypto.a"),
+    )
+    .expect("isa-l_crypto build produced no bin/isa-l_crypto.a");
+
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib=static=isal_crypto");
+    println!("cargo:rerun-if-changed=isa-l_crypto/");
+
+    vec![src.join("include")]
+}
+
+/// Links a system libisal_crypto discovered through pkg-config.
+fn link_crypto_pkg_config() -> Vec<PathBuf> {
+    pkg_config::Config::new()
+        .statik(link_static())
+        .probe("libisal_crypto")
+        .unwrap_or_else(|e| panic!("failed to find libisal_crypto via pkg-config: {e}"))
+        .include_paths
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap_or_else(|e| panic!("{}: {e}", from.display())) {
+        let entry = entry.unwrap();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+/// Links a system libisal discovered through pkg-config.
+fn link_pkg_config() -> Vec<PathBuf> {
+    let lib = pkg_config::Config::new()
+        .atleast_version(&format!("{ISAL_MAJOR_VERSION}.{ISAL_MINOR_VERSION}"))
+        .statik(link_static())
+        .probe("libisal")
+        .unwrap_or_else(|e| panic!("failed to find libisal via pkg-config: {e}"));
+
+    check_version(&lib.version);
+    lib.include_paths
+}
+
+/// Links libisal from `ISAL_LIB_DIR`, with headers from `ISAL_INCLUDE_DIR`.
+fn link_lib_dir(lib_dir: &Path) -> Vec<PathBuf> {
+    let kind = if link_static() { "static" } else { "dylib" };
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib={kind}=isal");
+
+    let include_dir = env::var_os("ISAL_INCLUDE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| lib_dir.join("../include"));
+
+    let api_h = [
+        include_dir.join("isa-l/isal_api.h"),
+        include_dir.join("isal_api.h"),
+    ];
+    match api_h.iter().find_map(|p| fs::read_to_string(p).ok()) {
+        Some(header) => check_version(&header_version(&header)),
+        None => println!(
+            "cargo:warning=isal_api.h not found under {}; skipping ISA-L version check",
+            include_dir.display()
+        ),
+    }
+    vec![include_dir]
+}
+
+/// `ISAL_STATIC=1` links a system libisal statically; dynamic by default.
+fn link_static() -> bool {
+    matches!(env::var("ISAL_STATIC").as_deref(), Ok("1") | Ok("true"))
+}
+
+/// Extracts "major.minor.patch" from the ISAL_*_VERSION defines in isal_api.h.
+fn header_version(header: &str) -> String {
+    let define = |name: &str| {
+        header
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix("#define"))
+            .find_map(|l| {
+                let mut it = l.split_whitespace();
+                (it.next() == Some(name)).then(|| it.next().unwrap_or("0").to_string())
+            })
+            .unwrap_or_else(|| "0".to_string())
+    };
+    format!(
+        "{}.{}.{}",
+        define("ISAL_MAJOR_VERSION"),
+        define("ISAL_MINOR_VERSION"),
+        define("ISAL_PATCH_VERSION")
+    )
+}
+
+/// Requires the same major version and at least the bound minor version.
+fn check_version(version: &str) {
+    let mut parts = version.split('.').map(|p| p.parse::<u32>().unwrap_or(0));
+    let major = parts.next().unwrap_or(0);
+    let minor = parts.next().unwrap_or(0);
+    if major != ISAL_MAJOR_VERSION || minor < ISAL_MINOR_VERSION {
+        panic!(
+            "system ISA-L {version} is incompatible with these bindings \
+             (need {ISAL_MAJOR_VERSION}.x >= {ISAL_MAJOR_VERSION}.{ISAL_MINOR_VERSION})"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! isa-l_crypto bindings, enabled by the `crypto` feature.
+//!
+//! Re-exported at the crate root alongside the isa-l declarations.
+
+use std::os::raw::{c_int, c_long, c_void};
+
+// ---------------------------------------------------------------------------
+// Constants: multi_buffer.h — Hash context flags, status and errors
+// ---------------------------------------------------------------------------
+pub const HASH_UPDATE: c_int = 0x00;
+pub const HASH_FIRST: c_int = 0x01;
+pub const HASH_LAST: c_int = 0x02;
+pub const HASH_ENTIRE: c_int = 0x03;
+
+pub const HASH_CTX_STS_IDLE: c_int = 0x00;
+pub const HASH_CTX_STS_PROCESSING: c_int = 0x01;
+pub const HASH_CTX_STS_LAST: c_int = 0x02;
+pub const HASH_CTX_STS_COMPLETE: c_int = 0x04;
+
+pub const HASH_CTX_ERROR_NONE: c_int = 0;
+pub const HASH_CTX_ERROR_INVALID_FLAGS: c_int = -1;
+pub const HASH_CTX_ERROR_ALREADY_PROCESSING: c_int = -2;
+pub const HASH_CTX_ERROR_ALREADY_COMPLETED: c_int = -3;
+
+// ---------------------------------------------------------------------------
+// Constants: *_mb.h — Digest sizes (in words of the algorithm's word size)
+// ---------------------------------------------------------------------------
+pub const SHA1_DIGEST_NWORDS: usize = 5;
+pub const SHA256_DIGEST_NWORDS: usize = 8;
+pub const SHA512_DIGEST_NWORDS: usize = 8;
+pub const MD5_DIGEST_NWORDS: usize = 4;
+pub const SM3_DIGEST_NWORDS: usize = 8;
+
+// ---------------------------------------------------------------------------
+// Constants: mh_sha1.h / mh_sha256.h — Return values
+// ---------------------------------------------------------------------------
+pub const MH_SHA1_CTX_ERROR_NONE: c_int = 0;
+pub const MH_SHA1_CTX_ERROR_NULL: c_int = -1;
+pub const MH_SHA256_CTX_ERROR_NONE: c_int = 0;
+pub const MH_SHA256_CTX_ERROR_NULL: c_int = -1;
+
+// ---------------------------------------------------------------------------
+// Constants: aes_keyexp.h / aes_gcm.h — Key and block sizes
+// ---------------------------------------------------------------------------
+/// Expanded AES-128 key schedule size (11 round keys).
+pub const AES_128_EXP_KEY_LEN: usize = 16 * 11;
+/// Expanded AES-256 key schedule size (15 round keys).
+pub const AES_256_EXP_KEY_LEN: usize = 16 * 15;
+
+pub const GCM_IV_DATA_LEN: usize = 12;
+pub const GCM_BLOCK_LEN: usize = 16;
+pub const MAX_TAG_LEN: usize = 16;
+pub const GCM_128_KEY_LEN: usize = 16;
+pub const GCM_256_KEY_LEN: usize = 32;
+
+// ---------------------------------------------------------------------------
+// Constants: rolling_hashx.h — rolling_hash2_run() results and window limit
+// ---------------------------------------------------------------------------
+pub const FINGERPRINT_RET_HIT: c_int = 0;
+pub const FINGERPRINT_RET_MAX: c_int = 1;
+pub const FINGERPRINT_RET_OTHER: c_int = 2;
+pub const FINGERPRINT_MAX_WINDOW: u32 = 48;
+
+// ---------------------------------------------------------------------------
+// Opaque types for the multi-buffer hash, AES-GCM and rolling hash structs.
+//
+// Sizes and field access go through the isal_rs_* helpers in
+// src/crypto_helpers.c.
+// ---------------------------------------------------------------------------
+
+macro_rules! opaque {
+    ($($(#[$doc:meta])* $name:ident;)*) => {
+        $(
+            $(#[$doc])*
+            #[repr(C)]
+            pub struct $name {
+                _opaque: [u8; 0],
+            }
+        )*
+    };
+}
+
+opaque! {
+    /// Opaque type for `SHA1_HASH_CTX_MGR`.
+    SHA1_HASH_CTX_MGR;
+    /// Opaque type for `SHA1_HASH_CTX`.
+    SHA1_HASH_CTX;
+    /// Opaque type for `SHA256_HASH_CTX_MGR`.
+    SHA256_HASH_CTX_MGR;
+    /// Opaque type for `SHA256_HASH_CTX`.
+    SHA256_HASH_CTX;
+    /// Opaque type for `SHA512_HASH_CTX_MGR`.
+    SHA512_HASH_CTX_MGR;
+    /// Opaque type for `SHA512_HASH_CTX`.
+    SHA512_HASH_CTX;
+    /// Opaque type for `MD5_HASH_CTX_MGR`.
+    MD5_HASH_CTX_MGR;
+    /// Opaque type for `MD5_HASH_CTX`.
+    MD5_HASH_CTX;
+    /// Opaque type for `SM3_HASH_CTX_MGR`.
+    SM3_HASH_CTX_MGR;
+    /// Opaque type for `SM3_HASH_CTX`.
+    SM3_HASH_CTX;
+    /// Opaque type for `struct mh_sha1_ctx`.
+    mh_sha1_ctx;
+    /// Opaque type for `struct mh_sha256_ctx`.
+    mh_sha256_ctx;
+    /// Opaque type for `struct gcm_key_data` (expanded key and GHASH tables).
+    gcm_key_data;
+    /// Opaque type for `struct gcm_context_data` (per-message state).
+    gcm_context_data;
+    /// Opaque type for `struct rh_state2`.
+    rh_state2;
+}
+
+extern "C" {
+    // ======================================================================
+    // sha1_mb.h / sha256_mb.h / sha512_mb.h / md5_mb.h / sm3_mb.h
+    //
+    // submit() and flush() return a completed context, or NULL when every
+    // lane is still in flight (submit) or the manager is empty (flush).
+    // ======================================================================
+
+    pub fn sha1_ctx_mgr_init(mgr: *mut SHA1_HASH_CTX_MGR);
+    pub fn sha1_ctx_mgr_submit(
+        mgr: *mut SHA1_HASH_CTX_MGR,
+        ctx: *mut SHA1_HASH_CTX,
+        buffer: *const c_void,
+        len: u32,
+        flags: c_int,
+    ) -> *mut SHA1_HASH_CTX;
+    pub fn sha1_ctx_mgr_flush(mgr: *mut SHA1_HASH_CTX_MGR) -> *mut SHA1_HASH_CTX;
+
+    pub fn sha256_ctx_mgr_init(mgr: *mut SHA256_HASH_CTX_MGR);
+    pub fn sha256_ctx_mgr_submit(
+        mgr: *mut SHA256_HASH_CTX_MGR,
+        ctx: *mut SHA256_HASH_CTX,
+        buffer: *const c_void,
+        len: u32,
+        flags: c_int,
+    ) -> *mut SHA256_HASH_CTX;
+    pub fn sha256_ctx_mgr_flush(mgr: *mut SHA256_HASH_CTX_MGR) -> *mut SHA256_HASH_CTX;
+
+    pub fn sha512_ctx_mgr_init(mgr: *mut SHA512_HASH_CTX_MGR);
+    pub fn sha512_ctx_mgr_submit(
+        mgr: *mut SHA512_HASH_CTX_MGR,
+        ctx: *mut SHA512_HASH_CTX,
+        buffer: *const c_void,
+        len: u32,
+        flags: c_int,
+    ) -> *mut SHA512_HASH_CTX;
+    pub fn sha512_ctx_mgr_flush(mgr: *mut SHA512_HASH_CTX_MGR) -> *mut SHA512_HASH_CTX;
+
+    pub fn md5_ctx_mgr_init(mgr: *mut MD5_HASH_CTX_MGR);
+    pub fn md5_ctx_mgr_submit(
+        mgr: *mut MD5_HASH_CTX_MGR,
+        ctx: *mut MD5_HASH_CTX,
+        buffer: *const c_void,
+        len: u32,
+        flags: c_int,
+    ) -> *mut MD5_HASH_CTX;
+    pub fn md5_ctx_mgr_flush(mgr: *mut MD5_HASH_CTX_MGR) -> *mut MD5_HASH_CTX;
+
+    pub fn sm3_ctx_mgr_init(mgr: *mut SM3_HASH_CTX_MGR);
+    pub fn sm3_ctx_mgr_submit(
+        mgr: *mut SM3_HASH_CTX_MGR,
+        ctx: *mut SM3_HASH_CTX,
+        buffer: *const c_void,
+        len: u32,
+        flags: c_int,
+    ) -> *mut SM3_HASH_CTX;
+    pub fn sm3_ctx_mgr_flush(mgr: *mut SM3_HASH_CTX_MGR) -> *mut SM3_HASH_CTX;
+
+    // ======================================================================
+    // mh_sha1.h / mh_sha256.h — Multi-hash (single stream, SIMD lanes)
+    // ======================================================================
+
+    pub fn mh_sha1_init(ctx: *mut mh_sha1_ctx) -> c_int;
+    pub fn mh_sha1_update(ctx: *mut mh_sha1_ctx, buffer: *const c_void, len: u32) -> c_int;
+    pub fn mh_sha1_finalize(ctx: *mut mh_sha1_ctx, mh_sha1_digest: *mut c_void) -> c_int;
+
+    pub fn mh_sha256_init(ctx: *mut mh_sha256_ctx) -> c_int;
+    pub fn mh_sha256_update(ctx: *mut mh_sha256_ctx, buffer: *const c_void, len: u32) -> c_int;
+    pub fn mh_sha256_finalize(ctx: *mut mh_sha256_ctx, mh_sha256_digest: *mut c_void) -> c_int;
+
+    // ======================================================================
+    // rolling_hashx.h — Rolling hash for content-defined chunking
+    //
+    // run() scans buffer for a position whose hash satisfies
+    // (hash & mask) == trigger and stores it in *offset; it returns
+    // FINGERPRINT_RET_HIT, or FINGERPRINT_RET_MAX with *offset set to
+    // buffer_length when the buffer ends first.
+    // ======================================================================
+
+    pub fn rolling_hash2_init(state: *mut rh_state2, w: u32) -> c_int;
+    pub fn rolling_hash2_reset(state: *mut rh_state2, init_bytes: *mut u8);
+    pub fn rolling_hash2_run(
+        state: *mut rh_state2,
+        buffer: *mut u8,
+        buffer_length: u32,
+        mask: u32,
+        trigger: u32,
+        offset: *mut u32,
+    ) -> c_int;
+    /// Mask with roughly log2(mean) bits set, for an expected hit spacing of
+    /// `mean` bytes; `shift` rotates the chosen bits.
+    pub fn rolling_hashx_mask_gen(mean: c_long, shift: c_int) -> u32;
+
+    // ======================================================================
+    // aes_keyexp.h — Key expansion
+    // ======================================================================
+
+    pub fn aes_keyexp_128(key: *const u8, exp_key_enc: *mut u8, exp_key_dec: *mut u8);
+    pub fn aes_keyexp_256(key: *const u8, exp_key_enc: *mut u8, exp_key_dec: *mut u8);
+
+    // ======================================================================
+    // aes_xts.h — XTS-AES (IEEE 1619)
+    //
+    // k2 is the tweak key, k1 the data key. The _expanded_key variants take
+    // the encryption schedule for k2 and, when decrypting, the decryption
+    // schedule for k1. n is the data-unit length (>= 16 bytes).
+    // ======================================================================
+
+    pub fn XTS_AES_128_enc(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        pt: *const u8,
+        ct: *mut u8,
+    );
+    pub fn XTS_AES_128_enc_expanded_key(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        pt: *const u8,
+        ct: *mut u8,
+    );
+    pub fn XTS_AES_128_dec(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        ct: *const u8,
+        pt: *mut u8,
+    );
+    pub fn XTS_AES_128_dec_expanded_key(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        ct: *const u8,
+        pt: *mut u8,
+    );
+    pub fn XTS_AES_256_enc(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        pt: *const u8,
+        ct: *mut u8,
+    );
+    pub fn XTS_AES_256_enc_expanded_key(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        pt: *const u8,
+        ct: *mut u8,
+    );
+    pub fn XTS_AES_256_dec(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        ct: *const u8,
+        pt: *mut u8,
+    );
+    pub fn XTS_AES_256_dec_expanded_key(
+        k2: *mut u8,
+        k1: *mut u8,
+        tw_initial: *mut u8,
+        n: u64,
+        ct: *const u8,
+        pt: *mut u8,
+    );
+
+    // ======================================================================
+    // aes_gcm.h — AES-GCM (12-byte IV)
+    //
+    // aes_gcm_dec_* writes the computed tag to auth_tag; the caller compares.
+    // ======================================================================
+
+    pub fn aes_gcm_pre_128(key: *const c_void, key_data: *mut gcm_key_data);
+    pub fn aes_gcm_pre_256(key: *const c_void, key_data: *mut gcm_key_data);
+    pub fn aes_gcm_enc_128(
+        key_data: *const gcm_key_data,
+        context_data: *mut gcm_context_data,
+        out: *mut u8,
+        in_: *const u8,
+        len: u64,
+        iv: *mut u8,
+        aad: *const u8,
+        aad_len: u64,
+        auth_tag: *mut u8,
+        auth_tag_len: u64,
+    );
+    pub fn aes_gcm_dec_128(
+        key_data: *const gcm_key_data,
+        context_data: *mut gcm_context_data,
+        out: *mut u8,
+        in_: *const u8,
+        len: u64,
+        iv: *mut u8,
+        aad: *const u8,
+        aad_len: u64,
+        auth_tag: *mut u8,
+        auth_tag_len: u64,
+    );
+    pub fn aes_gcm_enc_256(
+        key_data: *const gcm_key_data,
+        context_data: *mut gcm_context_data,
+        out: *mut u8,
+        in_: *const u8,
+        len: u64,
+        iv: *mut u8,
+        aad: *const u8,
+        aad_len: u64,
+        auth_tag: *mut u8,
+        auth_tag_len: u64,
+    );
+    pub fn aes_gcm_dec_256(
+        key_data: *const gcm_key_data,
+        context_data: *mut gcm_context_data,
+        out: *mut u8,
+        in_: *const u8,
+        len: u64,
+        iv: *mut u8,
+        aad: *const u8,
+        aad_len: u64,
+        auth_tag: *mut u8,
+        auth_tag_len: u64,
+    );
+}
+
+// ===========================================================================
+// Helper: sizeof queries and field accessors (src/crypto_helpers.c)
+// ===========================================================================
+
+macro_rules! mb_hash_helpers {
+    ($($ctx:ident, $mgr:ident {
+        $mgr_size:ident, $mgr_align:ident, $ctx_size:ident, $ctx_align:ident,
+        $init:ident, $status:ident, $error:ident, $digest:ident,
+        $user_data:ident, $set_user_data:ident
+    })*) => {
+        extern "C" {
+            $(
+                pub fn $mgr_size() -> usize;
+                pub fn $mgr_align() -> usize;
+                pub fn $ctx_size() -> usize;
+                pub fn $ctx_align() -> usize;
+                /// `hash_ctx_init()`: marks the context complete and error-free.
+                pub fn $init(ctx: *mut $ctx);
+                /// One of the `HASH_CTX_STS_*` values.
+                pub fn $status(ctx: *const $ctx) -> c_int;
+                /// One of the `HASH_CTX_ERROR_*` values.
+                pub fn $error(ctx: *const $ctx) -> c_int;
+                /// Pointer to `job.result_digest` (native-endian words).
+                pub fn $digest(ctx: *const $ctx) -> *const c_void;
+                pub fn $user_data(ctx: *const $ctx) -> *mut c_void;
+                pub fn $set_user_data(ctx: *mut $ctx, user_data: *mut c_void);
+            )*
+        }
+    };
+}
+
+mb_hash_helpers! {
+    SHA1_HASH_CTX, SHA1_HASH_CTX_MGR {
+        isal_rs_sha1_ctx_mgr_size, isal_rs_sha1_ctx_mgr_align,
+        isal_rs_sha1_ctx_size, isal_rs_sha1_ctx_align,
+        isal_rs_sha1_ctx_init, isal_rs_sha1_ctx_status, isal_rs_sha1_ctx_error,
+        isal_rs_sha1_ctx_digest, isal_rs_sha1_ctx_user_data, isal_rs_sha1_ctx_set_user_data
+    }
+    SHA256_HASH_CTX, SHA256_HASH_CTX_MGR {
+        isal_rs_sha256_ctx_mgr_size, isal_rs_sha256_ctx_mgr_align,
+        isal_rs_sha256_ctx_size, isal_rs_sha256_ctx_align,
+        isal_rs_sha256_ctx_init, isal_rs_sha256_ctx_status, isal_rs_sha256_ctx_error,
+        isal_rs_sha256_ctx_digest, isal_rs_sha256_ctx_user_data, isal_rs_sha256_ctx_set_user_data
+    }
+    SHA512_HASH_CTX, SHA512_HASH_CTX_MGR {
+        isal_rs_sha512_ctx_mgr_size, isal_rs_sha512_ctx_mgr_align,
+        isal_rs_sha512_ctx_size, isal_rs_sha512_ctx_align,
+        isal_rs_sha512_ctx_init, isal_rs_sha512_ctx_status, isal_rs_sha512_ctx_error,
+        isal_rs_sha512_ctx_digest, isal_rs_sha512_ctx_user_data, isal_rs_sha512_ctx_set_user_data
+    }
+    MD5_HASH_CTX, MD5_HASH_CTX_MGR {
+        isal_rs_md5_ctx_mgr_size, isal_rs_md5_ctx_mgr_align,
+        isal_rs_md5_ctx_size, isal_rs_md5_ctx_align,
+        isal_rs_md5_ctx_init, isal_rs_md5_ctx_status, isal_rs_md5_ctx_error,
+        isal_rs_md5_ctx_digest, isal_rs_md5_ctx_user_data, isal_rs_md5_ctx_set_user_data
+    }
+    SM3_HASH_CTX, SM3_HASH_CTX_MGR {
+        isal_rs_sm3_ctx_mgr_size, isal_rs_sm3_ctx_mgr_align,
+        isal_rs_sm3_ctx_size, isal_rs_sm3_ctx_align,
+        isal_rs_sm3_ctx_init, isal_rs_sm3_ctx_status, isal_rs_sm3_ctx_error,
+        isal_rs_sm3_ctx_digest, isal_rs_sm3_ctx_user_data, isal_rs_sm3_ctx_set_user_data
+    }
+}
+
+extern "C" {
+    pub fn isal_rs_mh_sha1_ctx_size() -> usize;
+    pub fn isal_rs_mh_sha1_ctx_align() -> usize;
+    pub fn isal_rs_mh_sha256_ctx_size() -> usize;
+    pub fn isal_rs_mh_sha256_ctx_align() -> usize;
+
+    pub fn isal_rs_gcm_key_data_size() -> usize;
+    pub fn isal_rs_gcm_key_data_align() -> usize;
+    pub fn isal_rs_gcm_context_data_size() -> usize;
+    pub fn isal_rs_gcm_context_data_align() -> usize;
+
+    pub fn isal_rs_rh_state2_size() -> usize;
+    pub fn isal_rs_rh_state2_align() -> usize;
+}
@@ -0,0 +1,66 @@
+/*
+ * C shim for the isa-l_crypto multi-buffer hash contexts, the AES-GCM key and
+ * context structs and the rolling hash state, compiled by build.rs when the
+ * `crypto` feature is enabled.
+ *
+ * The *_HASH_CTX / *_HASH_CTX_MGR, gcm_* and rh_state2 structs are exposed to
+ * Rust as opaque types; their sizes, the hash_ctx_init() macro and the fields
+ * the safe wrappers read are reached through these functions.
+ */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include "aes_gcm.h"
+#include "md5_mb.h"
+#include "mh_sha1.h"
+#include "mh_sha256.h"
+#include "rolling_hashx.h"
+#include "sha1_mb.h"
+#include "sha256_mb.h"
+#include "sha512_mb.h"
+#include "sm3_mb.h"
+
+#define MB_HASH_HELPERS(name, CTX, MGR)                                                  \
+	size_t isal_rs_##name##_ctx_mgr_size(void) { return sizeof(MGR); }               \
+	size_t isal_rs_##name##_ctx_mgr_align(void) { return _Alignof(MGR); }            \
+	size_t isal_rs_##name##_ctx_size(void) { return sizeof(CTX); }                   \
+	size_t isal_rs_##name##_ctx_align(void) { return _Alignof(CTX); }                \
+	void isal_rs_##name##_ctx_init(CTX *ctx) { hash_ctx_init(ctx); }                 \
+	int isal_rs_##name##_ctx_status(const CTX *ctx) { return ctx->status; }          \
+	int isal_rs_##name##_ctx_error(const CTX *ctx) { return ctx->error; }            \
+	const void *isal_rs_##name##_ctx_digest(const CTX *ctx)                          \
+	{                                                                                \
+		return ctx->job.result_digest;                                           \
+	}                                                                                \
+	void *isal_rs_##name##_ctx_user_data(const CTX *ctx) { return ctx->user_data; } \
+	void isal_rs_##name##_ctx_set_user_data(CTX *ctx, void *user_data)               \
+	{                                                                                \
+		ctx->user_data = user_data;                                              \
+	}
+
+MB_HASH_HELPERS(sha1, SHA1_HASH_CTX, SHA1_HASH_CTX_MGR)
+MB_HASH_HELPERS(sha256, SHA256_HASH_CTX, SHA256_HASH_CTX_MGR)
+MB_HASH_HELPERS(sha512, SHA512_HASH_CTX, SHA512_HASH_CTX_MGR)
+MB_HASH_HELPERS(md5, MD5_HASH_CTX, MD5_HASH_CTX_MGR)
+MB_HASH_HELPERS(sm3, SM3_HASH_CTX, SM3_HASH_CTX_MGR)
+
+size_t isal_rs_mh_sha1_ctx_size(void) { return sizeof(struct mh_sha1_ctx); }
+
+size_t isal_rs_mh_sha1_ctx_align(void) { return _Alignof(struct mh_sha1_ctx); }
+
+size_t isal_rs_mh_sha256_ctx_size(void) { return sizeof(struct mh_sha256_ctx); }
+
+size_t isal_rs_mh_sha256_ctx_align(void) { return _Alignof(struct mh_sha256_ctx); }
+
+size_t isal_rs_gcm_key_data_size(void) { return sizeof(struct gcm_key_data); }
+
+size_t isal_rs_gcm_key_data_align(void) { return _Alignof(struct gcm_key_data); }
+
+size_t isal_rs_gcm_context_data_size(void) { return sizeof(struct gcm_context_data); }
+
+size_t isal_rs_gcm_context_data_align(void) { return _Alignof(struct gcm_context_data); }
+
+size_t isal_rs_rh_state2_size(void) { return sizeof(struct rh_state2); }
+
+size_t isal_rs_rh_state2_align(void) { return _Alignof(struct rh_state2); }
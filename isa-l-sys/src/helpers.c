@@ -0,0 +1,101 @@
+/*
+ * Small C shim compiled by build.rs next to libisal.
+ *
+ * The igzip structs are exposed to Rust as opaque types, so their sizes and
+ * the handful of public fields the safe wrappers need are reached through
+ * these accessors instead of mirroring the full C layout in Rust.
+ */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#include "igzip_lib.h"
+
+/* ------------------------------------------------------------------------ */
+/* sizeof / alignof                                                          */
+/* ------------------------------------------------------------------------ */
+
+size_t isal_rs_zstream_size(void) { return sizeof(struct isal_zstream); }
+
+size_t isal_rs_zstream_align(void) { return _Alignof(struct isal_zstream); }
+
+size_t isal_rs_inflate_state_size(void) { return sizeof(struct inflate_state); }
+
+size_t isal_rs_inflate_state_align(void) { return _Alignof(struct inflate_state); }
+
+/* ------------------------------------------------------------------------ */
+/* struct isal_zstream                                                       */
+/* ------------------------------------------------------------------------ */
+
+void isal_rs_zstream_set_in(struct isal_zstream *s, uint8_t *next_in, uint32_t avail_in)
+{
+	s->next_in = next_in;
+	s->avail_in = avail_in;
+}
+
+void isal_rs_zstream_set_out(struct isal_zstream *s, uint8_t *next_out, uint32_t avail_out)
+{
+	s->next_out = next_out;
+	s->avail_out = avail_out;
+}
+
+uint32_t isal_rs_zstream_avail_in(const struct isal_zstream *s) { return s->avail_in; }
+
+uint32_t isal_rs_zstream_avail_out(const struct isal_zstream *s) { return s->avail_out; }
+
+uint32_t isal_rs_zstream_total_in(const struct isal_zstream *s) { return s->total_in; }
+
+uint32_t isal_rs_zstream_total_out(const struct isal_zstream *s) { return s->total_out; }
+
+void isal_rs_zstream_set_level(struct isal_zstream *s, uint32_t level, uint8_t *level_buf,
+			       uint32_t level_buf_size)
+{
+	s->level = level;
+	s->level_buf = level_buf;
+	s->level_buf_size = level_buf_size;
+}
+
+void isal_rs_zstream_set_flags(struct isal_zstream *s, uint16_t end_of_stream, uint16_t flush,
+			       uint16_t gzip_flag)
+{
+	s->end_of_stream = end_of_stream;
+	s->flush = flush;
+	s->gzip_flag = gzip_flag;
+}
+
+int isal_rs_zstream_finished(const struct isal_zstream *s)
+{
+	return s->internal_state.state == ZSTATE_END;
+}
+
+/* ------------------------------------------------------------------------ */
+/* struct inflate_state                                                      */
+/* ------------------------------------------------------------------------ */
+
+void isal_rs_inflate_set_in(struct inflate_state *s, uint8_t *next_in, uint32_t avail_in)
+{
+	s->next_in = next_in;
+	s->avail_in = avail_in;
+}
+
+void isal_rs_inflate_set_out(struct inflate_state *s, uint8_t *next_out, uint32_t avail_out)
+{
+	s->next_out = next_out;
+	s->avail_out = avail_out;
+}
+
+uint32_t isal_rs_inflate_avail_in(const struct inflate_state *s) { return s->avail_in; }
+
+uint32_t isal_rs_inflate_avail_out(const struct inflate_state *s) { return s->avail_out; }
+
+uint32_t isal_rs_inflate_total_out(const struct inflate_state *s) { return s->total_out; }
+
+void isal_rs_inflate_set_crc_flag(struct inflate_state *s, uint32_t crc_flag)
+{
+	s->crc_flag = crc_flag;
+}
+
+int isal_rs_inflate_finished(const struct inflate_state *s)
+{
+	return s->block_state == ISAL_BLOCK_FINISH;
+}
@@ -0,0 +1,13 @@
+/*
+ * Headers fed to bindgen when the `bindgen` feature is enabled. The generated
+ * bindings are only used to check the hand-written declarations in lib.rs.
+ */
+
+#include "isal_api.h"
+#include "crc.h"
+#include "crc64.h"
+#include "erasure_code.h"
+#include "gf_vect_mul.h"
+#include "igzip_lib.h"
+#include "mem_routines.h"
+#include "raid.h"
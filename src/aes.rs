@@ -0,0 +1,541 @@
+//! AES-XTS and AES-GCM from isa-l_crypto (`crypto` feature).
+//!
+//! [`XtsAes128`] / [`XtsAes256`] encrypt fixed-size data units (sectors)
+//! under IEEE 1619 XTS; the tweak is normally the sector number, see
+//! [`sector_tweak`]. [`AesGcm128`] / [`AesGcm256`] provide authenticated
+//! encryption with 12-byte nonces and 16-byte tags.
+//!
+//! Expanded keys are kept for the lifetime of the value and overwritten with
+//! zeros on drop.
+
+use std::os::raw::c_void;
+use std::ptr;
+
+use crate::error::AesError;
+use crate::raw::RawState;
+use crate::sys::*;
+
+/// AES block size; the shortest XTS data unit.
+pub const AES_BLOCK_LEN: usize = 16;
+
+/// GCM nonce length.
+pub const GCM_NONCE_LEN: usize = GCM_IV_DATA_LEN;
+
+/// GCM tag length produced by [`AesGcm128::seal`] and friends.
+pub const GCM_TAG_LEN: usize = MAX_TAG_LEN;
+
+/// IEEE 1619 tweak for data unit `sector`: the sector number as a 128-bit
+/// little-endian integer.
+pub fn sector_tweak(sector: u64) -> [u8; 16] {
+    let mut tweak = [0u8; 16];
+    tweak[..8].copy_from_slice(&sector.to_le_bytes());
+    tweak
+}
+
+/// Overwrites `len` bytes at `p` with zeros in a way the optimizer keeps.
+unsafe fn wipe(p: *mut u8, len: usize) {
+    for i in 0..len {
+        ptr::write_volatile(p.add(i), 0);
+    }
+}
+
+macro_rules! xts {
+    ($(#[$doc:meta])* $name:ident, $key_len:expr, $exp_len:ident, $keyexp:ident,
+     $enc:ident, $dec:ident) => {
+        $(#[$doc])*
+        pub struct $name {
+            /// Tweak key (k2) encryption schedule.
+            tweak_enc: [u8; $exp_len],
+            /// Data key (k1) encryption and decryption schedules.
+            data_enc: [u8; $exp_len],
+            data_dec: [u8; $exp_len],
+        }
+
+        impl $name {
+            /// Expands `data_key` (k1) and `tweak_key` (k2).
+            pub fn new(data_key: &[u8; $key_len], tweak_key: &[u8; $key_len]) -> Self {
+                let mut xts = $name {
+                    tweak_enc: [0; $exp_len],
+                    data_enc: [0; $exp_len],
+                    data_dec: [0; $exp_len],
+                };
+                // The decryption schedule for k2 is never used.
+                let mut unused = [0u8; $exp_len];
+                unsafe {
+                    $keyexp(data_key.as_ptr(), xts.data_enc.as_mut_ptr(), xts.data_dec.as_mut_ptr());
+                    $keyexp(tweak_key.as_ptr(), xts.tweak_enc.as_mut_ptr(), unused.as_mut_ptr());
+                    wipe(unused.as_mut_ptr(), $exp_len);
+                }
+                xts
+            }
+
+            /// Encrypts one data unit of `src` into `dst` under `tweak`.
+            ///
+            /// # Errors
+            /// [`AesError::TooShort`] if `src` is shorter than one AES block.
+            ///
+            /// # Panics
+            /// Panics if `dst` and `src` differ in length.
+            pub fn encrypt_sector(
+                &self,
+                tweak: &[u8; 16],
+                src: &[u8],
+                dst: &mut [u8],
+            ) -> Result<(), AesError> {
+                check_sector(src, dst)?;
+                let mut tw = *tweak;
+                unsafe {
+                    $enc(
+                        self.tweak_enc.as_ptr() as *mut u8,
+                        self.data_enc.as_ptr() as *mut u8,
+                        tw.as_mut_ptr(),
+                        src.len() as u64,
+                        src.as_ptr(),
+                        dst.as_mut_ptr(),
+                    )
+                };
+                Ok(())
+            }
+
+            /// Decrypts one data unit of `src` into `dst` under `tweak`.
+            ///
+            /// # Errors
+            /// [`AesError::TooShort`] if `src` is shorter than one AES block.
+            ///
+            /// # Panics
+            /// Panics if `dst` and `src` differ in length.
+            pub fn decrypt_sector(
+                &self,
+                tweak: &[u8; 16],
+                src: &[u8],
+                dst: &mut [u8],
+            ) -> Result<(), AesError> {
+                check_sector(src, dst)?;
+                let mut tw = *tweak;
+                unsafe {
+                    $dec(
+                        self.tweak_enc.as_ptr() as *mut u8,
+                        self.data_dec.as_ptr() as *mut u8,
+                        tw.as_mut_ptr(),
+                        src.len() as u64,
+                        src.as_ptr(),
+                        dst.as_mut_ptr(),
+                    )
+                };
+                Ok(())
+            }
+
+            /// Encrypts `src` as consecutive `sector_size` data units numbered
+            /// from `first_sector`. The last unit may be short but not below
+            /// one AES block.
+            ///
+            /// # Panics
+            /// Panics if `dst` and `src` differ in length or `sector_size` is
+            /// zero.
+            pub fn encrypt_sectors(
+                &self,
+                first_sector: u64,
+                sector_size: usize,
+                src: &[u8],
+                dst: &mut [u8],
+            ) -> Result<(), AesError> {
+                assert_eq!(dst.len(), src.len(), "dst and src must be the same length");
+                for (i, (s, d)) in src.chunks(sector_size).zip(dst.chunks_mut(sector_size)).enumerate() {
+                    self.encrypt_sector(&sector_tweak(first_sector + i as u64), s, d)?;
+                }
+                Ok(())
+            }
+
+            /// Inverse of [`encrypt_sectors`](Self::encrypt_sectors).
+            ///
+            /// # Panics
+            /// Panics if `dst` and `src` differ in length or `sector_size` is
+            /// zero.
+            pub fn decrypt_sectors(
+                &self,
+                first_sector: u64,
+                sector_size: usize,
+                src: &[u8],
+                dst: &mut [u8],
+            ) -> Result<(), AesError> {
+                assert_eq!(dst.len(), src.len(), "dst and src must be the same length");
+                for (i, (s, d)) in src.chunks(sector_size).zip(dst.chunks_mut(sector_size)).enumerate() {
+                    self.decrypt_sector(&sector_tweak(first_sector + i as u64), s, d)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                unsafe {
+                    wipe(self.tweak_enc.as_mut_ptr(), $exp_len);
+                    wipe(self.data_enc.as_mut_ptr(), $exp_len);
+                    wipe(self.data_dec.as_mut_ptr(), $exp_len);
+                }
+            }
+        }
+    };
+}
+
+xts!(
+    /// XTS-AES-128 with separate 16-byte data and tweak keys.
+    XtsAes128, 16, AES_128_EXP_KEY_LEN, aes_keyexp_128,
+    XTS_AES_128_enc_expanded_key, XTS_AES_128_dec_expanded_key
+);
+
+xts!(
+    /// XTS-AES-256 with separate 32-byte data and tweak keys.
+    XtsAes256, 32, AES_256_EXP_KEY_LEN, aes_keyexp_256,
+    XTS_AES_256_enc_expanded_key, XTS_AES_256_dec_expanded_key
+);
+
+fn check_sector(src: &[u8], dst: &[u8]) -> Result<(), AesError> {
+    assert_eq!(dst.len(), src.len(), "dst and src must be the same length");
+    if src.len() < AES_BLOCK_LEN {
+        return Err(AesError::TooShort {
+            len: src.len(),
+            min: AES_BLOCK_LEN,
+        });
+    }
+    Ok(())
+}
+
+/// Compares two tags without an early exit.
+fn tags_equal(a: &[u8; GCM_TAG_LEN], b: &[u8; GCM_TAG_LEN]) -> bool {
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn new_context() -> RawState<gcm_context_data> {
+    unsafe {
+        RawState::new(
+            isal_rs_gcm_context_data_size(),
+            isal_rs_gcm_context_data_align(),
+        )
+    }
+}
+
+macro_rules! gcm {
+    ($(#[$doc:meta])* $name:ident, $key_len:expr, $pre:ident, $enc:ident, $dec:ident) => {
+        $(#[$doc])*
+        pub struct $name {
+            key: RawState<gcm_key_data>,
+        }
+
+        // SAFETY: the key data is written once in `new` and only read after.
+        unsafe impl Send for $name {}
+        unsafe impl Sync for $name {}
+
+        impl $name {
+            /// Expands `key` and precomputes the GHASH tables.
+            pub fn new(key: &[u8; $key_len]) -> Self {
+                let state =
+                    unsafe { RawState::new(isal_rs_gcm_key_data_size(), isal_rs_gcm_key_data_align()) };
+                unsafe { $pre(key.as_ptr() as *const c_void, state.as_ptr()) };
+                $name { key: state }
+            }
+
+            /// Encrypts `src` into `dst`, authenticating `aad` as well, and
+            /// returns the tag.
+            ///
+            /// # Panics
+            /// Panics if `dst` and `src` differ in length.
+            pub fn seal_detached(
+                &self,
+                nonce: &[u8; GCM_NONCE_LEN],
+                aad: &[u8],
+                src: &[u8],
+                dst: &mut [u8],
+            ) -> [u8; GCM_TAG_LEN] {
+                assert_eq!(dst.len(), src.len(), "dst and src must be the same length");
+                let ctx = new_context();
+                let mut iv = *nonce;
+                let mut tag = [0u8; GCM_TAG_LEN];
+                unsafe {
+                    $enc(
+                        self.key.as_ptr(),
+                        ctx.as_ptr(),
+                        dst.as_mut_ptr(),
+                        src.as_ptr(),
+                        src.len() as u64,
+                        iv.as_mut_ptr(),
+                        aad.as_ptr(),
+                        aad.len() as u64,
+                        tag.as_mut_ptr(),
+                        GCM_TAG_LEN as u64,
+                    );
+                    wipe(ctx.as_ptr() as *mut u8, ctx.size());
+                }
+                tag
+            }
+
+            /// Verifies `tag` over `aad` and `src` and decrypts `src` into
+            /// `dst`.
+            ///
+            /// # Errors
+            /// [`AesError::AuthenticationFailed`] if the tag does not match;
+            /// `dst` is zeroed in that case.
+            ///
+            /// # Panics
+            /// Panics if `dst` and `src` differ in length.
+            pub fn open_detached(
+                &self,
+                nonce: &[u8; GCM_NONCE_LEN],
+                aad: &[u8],
+                src: &[u8],
+                dst: &mut [u8],
+                tag: &[u8; GCM_TAG_LEN],
+            ) -> Result<(), AesError> {
+                assert_eq!(dst.len(), src.len(), "dst and src must be the same length");
+                let ctx = new_context();
+                let mut iv = *nonce;
+                let mut computed = [0u8; GCM_TAG_LEN];
+                unsafe {
+                    $dec(
+                        self.key.as_ptr(),
+                        ctx.as_ptr(),
+                        dst.as_mut_ptr(),
+                        src.as_ptr(),
+                        src.len() as u64,
+                        iv.as_mut_ptr(),
+                        aad.as_ptr(),
+                        aad.len() as u64,
+                        computed.as_mut_ptr(),
+                        GCM_TAG_LEN as u64,
+                    );
+                    wipe(ctx.as_ptr() as *mut u8, ctx.size());
+                }
+                if tags_equal(&computed, tag) {
+                    Ok(())
+                } else {
+                    unsafe { wipe(dst.as_mut_ptr(), dst.len()) };
+                    Err(AesError::AuthenticationFailed)
+                }
+            }
+
+            /// Encrypts `plaintext`, returning the ciphertext followed by the
+            /// 16-byte tag.
+            pub fn seal(&self, nonce: &[u8; GCM_NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+                let mut out = vec![0u8; plaintext.len() + GCM_TAG_LEN];
+                let (ct, tag) = out.split_at_mut(plaintext.len());
+                tag.copy_from_slice(&self.seal_detached(nonce, aad, plaintext, ct));
+                out
+            }
+
+            /// Verifies and decrypts the output of [`seal`](Self::seal).
+            ///
+            /// # Errors
+            /// [`AesError::TooShort`] if `sealed` cannot hold a tag, and
+            /// [`AesError::AuthenticationFailed`] if the tag does not match.
+            pub fn open(
+                &self,
+                nonce: &[u8; GCM_NONCE_LEN],
+                aad: &[u8],
+                sealed: &[u8],
+            ) -> Result<Vec<u8>, AesError> {
+                if sealed.len() < GCM_TAG_LEN {
+                    return Err(AesError::TooShort {
+                        len: sealed.len(),
+                        min: GCM_TAG_LEN,
+                    });
+                }
+                let (ct, tag) = sealed.split_at(sealed.len() - GCM_TAG_LEN);
+                let mut tag_arr = [0u8; GCM_TAG_LEN];
+                tag_arr.copy_from_slice(tag);
+                let mut out = vec![0u8; ct.len()];
+                self.open_detached(nonce, aad, ct, &mut out, &tag_arr)?;
+                Ok(out)
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                unsafe { wipe(self.key.as_ptr() as *mut u8, self.key.size()) };
+            }
+        }
+    };
+}
+
+gcm!(
+    /// AES-128-GCM.
+    AesGcm128, 16, aes_gcm_pre_128, aes_gcm_enc_128, aes_gcm_dec_128
+);
+
+gcm!(
+    /// AES-256-GCM.
+    AesGcm256, 32, aes_gcm_pre_256, aes_gcm_enc_256, aes_gcm_dec_256
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn arr<const N: usize>(s: &str) -> [u8; N] {
+        unhex(s).try_into().unwrap()
+    }
+
+    fn xts128_case(k1: &str, k2: &str, sector: u64, pt: &[u8], ct: &str) {
+        let xts = XtsAes128::new(&arr(k1), &arr(k2));
+        let mut out = vec![0u8; pt.len()];
+        xts.encrypt_sector(&sector_tweak(sector), pt, &mut out)
+            .unwrap();
+        assert_eq!(out, unhex(ct));
+        let mut back = vec![0u8; pt.len()];
+        xts.decrypt_sector(&sector_tweak(sector), &out, &mut back)
+            .unwrap();
+        assert_eq!(back, pt);
+    }
+
+    #[test]
+    fn test_xts_128_ieee1619_vectors() {
+        // Vector 1
+        xts128_case(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            0,
+            &[0; 32],
+            "917cf69ebd68b2ec9b9fe9a3eadda692cd43d2f59598ed858c02c2652fbf922e",
+        );
+        // Vector 2
+        xts128_case(
+            "11111111111111111111111111111111",
+            "22222222222222222222222222222222",
+            0x33_3333_3333,
+            &[0x44; 32],
+            "c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0",
+        );
+        // Vector 15: 17 bytes, exercising ciphertext stealing
+        xts128_case(
+            "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+            "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+            0x9a_7856_3412,
+            &(0..17).collect::<Vec<u8>>(),
+            "6c1625db4671522d3d7599601de7ca09ed",
+        );
+    }
+
+    #[test]
+    fn test_xts_256_ieee1619_vector() {
+        // Vector 10, first two blocks (XTS blocks are independent when the
+        // unit length is a multiple of 16).
+        let xts = XtsAes256::new(
+            &arr("2718281828459045235360287471352662497757247093699959574966967627"),
+            &arr("3141592653589793238462643383279502884197169399375105820974944592"),
+        );
+        let pt: Vec<u8> = (0..32).collect();
+        let mut ct = vec![0u8; 32];
+        xts.encrypt_sector(&sector_tweak(0xff), &pt, &mut ct)
+            .unwrap();
+        assert_eq!(
+            ct,
+            unhex("1c3b3a102f770386e4836c99e370cf9bea00803f5e482357a4ae12d414a3e63b")
+        );
+    }
+
+    #[test]
+    fn test_xts_sectors() {
+        let xts = XtsAes256::new(&[7; 32], &[9; 32]);
+        let data: Vec<u8> = (0..4096 + 100).map(|i| (i * 13) as u8).collect();
+        let mut ct = vec![0u8; data.len()];
+        xts.encrypt_sectors(10, 512, &data, &mut ct).unwrap();
+
+        // Sector 11 stands alone.
+        let mut one = vec![0u8; 512];
+        xts.encrypt_sector(&sector_tweak(11), &data[512..1024], &mut one)
+            .unwrap();
+        assert_eq!(one, &ct[512..1024]);
+
+        let mut back = vec![0u8; data.len()];
+        xts.decrypt_sectors(10, 512, &ct, &mut back).unwrap();
+        assert_eq!(back, data);
+
+        let mut short = [0u8; 15];
+        assert_eq!(
+            xts.encrypt_sector(&sector_tweak(0), &[0; 15], &mut short),
+            Err(AesError::TooShort { len: 15, min: 16 })
+        );
+    }
+
+    // McGrew & Viega, "The Galois/Counter Mode of Operation", test cases 2,
+    // 4, 14 and 16.
+    #[test]
+    fn test_gcm_vectors() {
+        let gcm = AesGcm128::new(&[0; 16]);
+        let sealed = gcm.seal(&[0; 12], &[], &[0; 16]);
+        assert_eq!(
+            sealed,
+            unhex("0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf")
+        );
+
+        let pt = unhex(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+             1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+        );
+        let aad = unhex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let nonce = arr("cafebabefacedbaddecaf888");
+
+        let gcm = AesGcm128::new(&arr("feffe9928665731c6d6a8f9467308308"));
+        let mut ct = vec![0u8; pt.len()];
+        let tag = gcm.seal_detached(&nonce, &aad, &pt, &mut ct);
+        assert_eq!(
+            ct,
+            unhex(
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+                 21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091"
+            )
+        );
+        assert_eq!(tag, arr("5bc94fbc3221a5db94fae95ae7121a47"));
+        let mut back = vec![0u8; ct.len()];
+        gcm.open_detached(&nonce, &aad, &ct, &mut back, &tag)
+            .unwrap();
+        assert_eq!(back, pt);
+
+        let gcm = AesGcm256::new(&[0; 32]);
+        assert_eq!(
+            gcm.seal(&[0; 12], &[], &[0; 16]),
+            unhex("cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919")
+        );
+
+        let gcm = AesGcm256::new(&arr(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+        ));
+        let sealed = gcm.seal(&nonce, &aad, &pt);
+        assert_eq!(
+            sealed,
+            unhex(
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+                 8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662\
+                 76fc6ece0f4e1768cddf8853bb2d551b"
+            )
+        );
+        assert_eq!(gcm.open(&nonce, &aad, &sealed).unwrap(), pt);
+    }
+
+    #[test]
+    fn test_gcm_rejects_tampering() {
+        let gcm = AesGcm256::new(&[3; 32]);
+        let nonce = [1; 12];
+        let mut sealed = gcm.seal(&nonce, b"hdr", b"shard payload");
+
+        assert_eq!(
+            gcm.open(&nonce, b"hdx", &sealed),
+            Err(AesError::AuthenticationFailed)
+        );
+        sealed[0] ^= 1;
+        assert_eq!(
+            gcm.open(&nonce, b"hdr", &sealed),
+            Err(AesError::AuthenticationFailed)
+        );
+        assert_eq!(
+            gcm.open(&nonce, b"hdr", &sealed[..5]),
+            Err(AesError::TooShort { len: 5, min: 16 })
+        );
+    }
+}
@@ -0,0 +1,614 @@
+//! Tokio `AsyncRead`/`AsyncWrite` adapters over igzip (`tokio` feature).
+//!
+//! Each adapter drives a configured [`Compressor`] or [`Decompressor`]
+//! through its step API, so the level and the gzip/zlib/raw format come from
+//! `Compressor::set_format` / `Decompressor::set_format`:
+//! - [`CompressReader`] / [`DecompressReader`]: read compressed or
+//!   decompressed bytes out of an `AsyncBufRead`
+//! - [`CompressWriter`] / [`DecompressWriter`]: compress or decompress
+//!   everything written into an inner `AsyncWrite`
+//!
+//! No poll hands igzip more than [`STEP_LEN`] bytes at a time, and a reader
+//! yields back to the executor after [`POLL_BUDGET`] bytes of input without
+//! output, so large bodies never monopolise a worker thread. The writers
+//! don't accept input until their previous output has been written, so a
+//! slow sink pushes back on the producer rather than buffering without
+//! bound.
+//!
+//! For HTTP bodies, `tokio_util::io::StreamReader` turns a stream of
+//! `Bytes` chunks into the `AsyncBufRead` the readers take, and
+//! `ReaderStream` turns a reader back into a body stream.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::igzip::{Compressor, Decompressor};
+use crate::sys::{NO_FLUSH, SYNC_FLUSH};
+
+/// Most input or output handed to igzip in one step.
+pub const STEP_LEN: usize = 64 * 1024;
+
+/// Input a reader consumes in one poll without producing output before it
+/// yields.
+pub const POLL_BUDGET: usize = 1 << 20;
+
+fn deflate_err(e: crate::error::DeflateError) -> io::Error {
+    io::Error::other(e)
+}
+
+fn inflate_err(e: crate::error::InflateError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Charges one step against `budget`, yielding once it runs out.
+fn spend(budget: &mut usize, consumed: usize, cx: &mut Context<'_>) -> Poll<()> {
+    *budget = budget.saturating_sub(consumed.max(1));
+    if *budget == 0 {
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    } else {
+        Poll::Ready(())
+    }
+}
+
+/// Streams the compressed form of an `AsyncBufRead`.
+pub struct CompressReader<R> {
+    inner: R,
+    compressor: Compressor,
+}
+
+impl<R: AsyncBufRead + Unpin> CompressReader<R> {
+    /// Compresses everything read from `inner` as one stream.
+    pub fn new(inner: R, mut compressor: Compressor) -> Self {
+        compressor.begin();
+        Self { inner, compressor }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the reader and the compressor for reuse.
+    pub fn into_inner(self) -> (R, Compressor) {
+        (self.inner, self.compressor)
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncRead for CompressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let mut budget = POLL_BUDGET;
+        while buf.remaining() > 0 && !this.compressor.is_finished() {
+            let input = ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?;
+            let end = input.is_empty();
+            let input = &input[..input.len().min(STEP_LEN)];
+            let out = buf.initialize_unfilled();
+            let out_len = out.len().min(STEP_LEN);
+            let (consumed, produced) = this
+                .compressor
+                .compress_step(input, &mut out[..out_len], NO_FLUSH, end)
+                .map_err(deflate_err)?;
+            Pin::new(&mut this.inner).consume(consumed);
+            buf.advance(produced);
+            if produced > 0 {
+                break;
+            }
+            ready!(spend(&mut budget, consumed, cx));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Streams the decompressed form of an `AsyncBufRead`.
+///
+/// Reading stops at the end of the compressed stream, leaving anything
+/// after it in the inner reader, unless
+/// [`set_multiple_members`](Self::set_multiple_members) is on.
+pub struct DecompressReader<R> {
+    inner: R,
+    decompressor: Decompressor,
+    multiple_members: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> DecompressReader<R> {
+    /// Decompresses the stream read from `inner`.
+    pub fn new(inner: R, mut decompressor: Decompressor) -> Self {
+        decompressor.begin();
+        Self {
+            inner,
+            decompressor,
+            multiple_members: false,
+        }
+    }
+
+    /// Keeps decoding streams back to back until the inner reader ends, as
+    /// multi-member gzip files (and `Content-Encoding: gzip` bodies) require.
+    pub fn set_multiple_members(&mut self, multiple_members: bool) {
+        self.multiple_members = multiple_members;
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the reader and the decompressor for reuse.
+    pub fn into_inner(self) -> (R, Decompressor) {
+        (self.inner, self.decompressor)
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncRead for DecompressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let mut budget = POLL_BUDGET;
+        while buf.remaining() > 0 {
+            if this.decompressor.is_finished() {
+                if !this.multiple_members
+                    || ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?.is_empty()
+                {
+                    break;
+                }
+                this.decompressor.begin();
+            }
+            let input = ready!(Pin::new(&mut this.inner).poll_fill_buf(cx))?;
+            let end = input.is_empty();
+            let input = &input[..input.len().min(STEP_LEN)];
+            let out = buf.initialize_unfilled();
+            let out_len = out.len().min(STEP_LEN);
+            let (consumed, produced) = this
+                .decompressor
+                .decompress_step(input, &mut out[..out_len])
+                .map_err(inflate_err)?;
+            Pin::new(&mut this.inner).consume(consumed);
+            buf.advance(produced);
+            if produced > 0 {
+                break;
+            }
+            // igzip may still have held back output when the input ran
+            // out; the stream is only truncated once that is drained too.
+            if end && !this.decompressor.is_finished() {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            ready!(spend(&mut budget, consumed, cx));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Output produced by a writer but not yet accepted by the inner sink.
+struct Pending {
+    buf: Box<[u8]>,
+    pos: usize,
+    end: usize,
+}
+
+impl Pending {
+    fn new() -> Self {
+        Self {
+            buf: vec![0; STEP_LEN].into_boxed_slice(),
+            pos: 0,
+            end: 0,
+        }
+    }
+
+    /// Writes out everything pending, leaving the whole buffer free.
+    fn poll_drain<W: AsyncWrite + Unpin>(
+        &mut self,
+        inner: &mut W,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        while self.pos < self.end {
+            let n = ready!(Pin::new(&mut *inner).poll_write(cx, &self.buf[self.pos..self.end]))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.pos += n;
+        }
+        self.pos = 0;
+        self.end = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Marks the first `produced` bytes of the buffer as pending, returning
+    /// whether the step filled it.
+    fn fill(&mut self, produced: usize) -> bool {
+        self.end = produced;
+        produced == self.buf.len()
+    }
+}
+
+/// Compresses everything written to it into an inner `AsyncWrite`.
+///
+/// `poll_flush` emits a sync flush so the peer can decode everything
+/// written so far; `poll_shutdown` finishes the stream before shutting down
+/// the inner writer.
+pub struct CompressWriter<W> {
+    inner: W,
+    compressor: Compressor,
+    pending: Pending,
+    flushed: bool,
+}
+
+impl<W: AsyncWrite + Unpin> CompressWriter<W> {
+    /// Compresses everything written into `inner` as one stream.
+    pub fn new(inner: W, mut compressor: Compressor) -> Self {
+        compressor.begin();
+        Self {
+            inner,
+            compressor,
+            pending: Pending::new(),
+            flushed: true,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the writer and the compressor for reuse. Output not yet
+    /// flushed is lost.
+    pub fn into_inner(self) -> (W, Compressor) {
+        (self.inner, self.compressor)
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for CompressWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.compressor.is_finished() {
+            return Poll::Ready(Err(io::Error::other("write after shutdown")));
+        }
+        loop {
+            ready!(this.pending.poll_drain(&mut this.inner, cx))?;
+            if data.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+            let input = &data[..data.len().min(STEP_LEN)];
+            let (consumed, produced) = this
+                .compressor
+                .compress_step(input, &mut this.pending.buf, NO_FLUSH, false)
+                .map_err(deflate_err)?;
+            this.pending.fill(produced);
+            if consumed > 0 {
+                this.flushed = false;
+                return Poll::Ready(Ok(consumed));
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.pending.poll_drain(&mut this.inner, cx))?;
+            if this.flushed || this.compressor.is_finished() {
+                break;
+            }
+            let (_, produced) = this
+                .compressor
+                .compress_step(&[], &mut this.pending.buf, SYNC_FLUSH, false)
+                .map_err(deflate_err)?;
+            this.flushed = !this.pending.fill(produced);
+        }
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.pending.poll_drain(&mut this.inner, cx))?;
+            if this.compressor.is_finished() {
+                break;
+            }
+            let (_, produced) = this
+                .compressor
+                .compress_step(&[], &mut this.pending.buf, NO_FLUSH, true)
+                .map_err(deflate_err)?;
+            this.pending.fill(produced);
+        }
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// Decompresses everything written to it into an inner `AsyncWrite`.
+///
+/// Writing past the end of the compressed stream is an
+/// [`InvalidData`](io::ErrorKind::InvalidData) error unless
+/// [`set_multiple_members`](Self::set_multiple_members) is on, and
+/// `poll_shutdown` fails with
+/// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) if the stream is
+/// incomplete.
+pub struct DecompressWriter<W> {
+    inner: W,
+    decompressor: Decompressor,
+    pending: Pending,
+    multiple_members: bool,
+    more_output: bool,
+}
+
+impl<W: AsyncWrite + Unpin> DecompressWriter<W> {
+    /// Decompresses the stream written into `inner`.
+    pub fn new(inner: W, mut decompressor: Decompressor) -> Self {
+        decompressor.begin();
+        Self {
+            inner,
+            decompressor,
+            pending: Pending::new(),
+            multiple_members: false,
+            more_output: false,
+        }
+    }
+
+    /// Accepts streams back to back, as multi-member gzip files require.
+    pub fn set_multiple_members(&mut self, multiple_members: bool) {
+        self.multiple_members = multiple_members;
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the writer and the decompressor for reuse. Output not yet
+    /// flushed is lost.
+    pub fn into_inner(self) -> (W, Decompressor) {
+        (self.inner, self.decompressor)
+    }
+
+    /// Writes out everything decoded so far, including output igzip is
+    /// still holding from a step that filled the buffer.
+    fn poll_drain_all(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            ready!(self.pending.poll_drain(&mut self.inner, cx))?;
+            if !self.more_output {
+                return Poll::Ready(Ok(()));
+            }
+            let (_, produced) = self
+                .decompressor
+                .decompress_step(&[], &mut self.pending.buf)
+                .map_err(inflate_err)?;
+            self.more_output = self.pending.fill(produced);
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for DecompressWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.poll_drain_all(cx))?;
+            if data.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+            if this.decompressor.is_finished() {
+                if !this.multiple_members {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "data after end of compressed stream",
+                    )));
+                }
+                this.decompressor.begin();
+            }
+            let input = &data[..data.len().min(STEP_LEN)];
+            let (consumed, produced) = this
+                .decompressor
+                .decompress_step(input, &mut this.pending.buf)
+                .map_err(inflate_err)?;
+            this.more_output = this.pending.fill(produced);
+            if consumed > 0 {
+                return Poll::Ready(Ok(consumed));
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain_all(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain_all(cx))?;
+        if !this.decompressor.is_finished() {
+            return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+        }
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sys::{IGZIP_GZIP, IGZIP_ZLIB, ISAL_INFLATE_GZIP, ISAL_INFLATE_ZLIB};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len as u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 27) as u8 ^ (i / 1000) as u8)
+            .collect()
+    }
+
+    fn gzip_compressor() -> Compressor {
+        let mut c = Compressor::new(1).unwrap();
+        c.set_format(IGZIP_GZIP);
+        c
+    }
+
+    fn gzip_decompressor() -> Decompressor {
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_GZIP);
+        d
+    }
+
+    #[tokio::test]
+    async fn test_reader_round_trip() {
+        let data = sample(300_000);
+        // Tiny fill_buf chunks exercise partial input on both sides.
+        let mut enc =
+            CompressReader::new(BufReader::with_capacity(7, &data[..]), gzip_compressor());
+        let mut compressed = Vec::new();
+        enc.read_to_end(&mut compressed).await.unwrap();
+        assert_eq!(gzip_decompressor().decompress(&compressed).unwrap(), data);
+
+        let mut dec = DecompressReader::new(
+            BufReader::with_capacity(13, &compressed[..]),
+            gzip_decompressor(),
+        );
+        let mut out = Vec::new();
+        dec.read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, data);
+
+        let truncated = &compressed[..compressed.len() - 4];
+        let mut dec = DecompressReader::new(truncated, gzip_decompressor());
+        let err = dec.read_to_end(&mut Vec::new()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_reader_small_reads() {
+        let data = sample(100_000);
+        let compressed = gzip_compressor().compress(&data).unwrap();
+        for read_len in 1..=16 {
+            let mut dec = DecompressReader::new(&compressed[..], gzip_decompressor());
+            let mut out = Vec::new();
+            let mut chunk = [0u8; 16];
+            loop {
+                let mut buf = ReadBuf::new(&mut chunk[..read_len]);
+                std::future::poll_fn(|cx| Pin::new(&mut dec).poll_read(cx, &mut buf))
+                    .await
+                    .unwrap();
+                if buf.filled().is_empty() {
+                    break;
+                }
+                out.extend_from_slice(buf.filled());
+            }
+            assert_eq!(out, data, "read_len {read_len}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reader_multiple_members() {
+        let mut c = gzip_compressor();
+        let mut members = c.compress(b"hello, ").unwrap();
+        members.extend(c.compress(b"world").unwrap());
+
+        let mut dec = DecompressReader::new(&members[..], gzip_decompressor());
+        let mut out = Vec::new();
+        dec.read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, b"hello, ");
+
+        let mut dec = DecompressReader::new(&members[..], gzip_decompressor());
+        dec.set_multiple_members(true);
+        let mut out = Vec::new();
+        dec.read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, b"hello, world");
+    }
+
+    #[tokio::test]
+    async fn test_writer_round_trip() {
+        let data = sample(300_000);
+        let mut c = Compressor::new(2).unwrap();
+        c.set_format(IGZIP_ZLIB);
+        let mut enc = CompressWriter::new(Vec::new(), c);
+        for chunk in data.chunks(10_007) {
+            enc.write_all(chunk).await.unwrap();
+        }
+        enc.shutdown().await.unwrap();
+        let (compressed, _) = enc.into_inner();
+
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_ZLIB);
+        assert_eq!(d.decompress(&compressed).unwrap(), data);
+
+        let mut dec = DecompressWriter::new(Vec::new(), d);
+        for chunk in compressed.chunks(999) {
+            dec.write_all(chunk).await.unwrap();
+        }
+        dec.shutdown().await.unwrap();
+        assert_eq!(dec.get_ref(), &data);
+
+        let mut dec = DecompressWriter::new(Vec::new(), dec.into_inner().1);
+        dec.write_all(&compressed[..compressed.len() / 2])
+            .await
+            .unwrap();
+        let err = dec.shutdown().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_writer_flush_is_decodable() {
+        let mut enc = CompressWriter::new(Vec::new(), Compressor::new(1).unwrap());
+        enc.write_all(b"first message").await.unwrap();
+        enc.flush().await.unwrap();
+        let flushed = enc.get_ref().clone();
+        enc.flush().await.unwrap();
+        assert_eq!(enc.get_ref().len(), flushed.len());
+
+        let mut d = Decompressor::new();
+        d.begin();
+        let mut out = vec![0; 64];
+        let (consumed, produced) = d.decompress_step(&flushed, &mut out).unwrap();
+        assert_eq!(consumed, flushed.len());
+        assert_eq!(&out[..produced], b"first message");
+        assert!(!d.is_finished());
+
+        enc.shutdown().await.unwrap();
+        let err = enc.write(b"more").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+
+    #[tokio::test]
+    async fn test_writer_back_pressure() {
+        // A 1 KiB pipe with nobody reading yet: the encoder must stall
+        // instead of buffering the whole input.
+        let (mut rx, tx) = tokio::io::duplex(1024);
+        let data = sample(1 << 20);
+        let expected = data.clone();
+        let writer = tokio::spawn(async move {
+            let mut enc = CompressWriter::new(tx, gzip_compressor());
+            enc.write_all(&data).await.unwrap();
+            enc.shutdown().await.unwrap();
+        });
+        let mut compressed = Vec::new();
+        rx.read_to_end(&mut compressed).await.unwrap();
+        writer.await.unwrap();
+        assert_eq!(
+            gzip_decompressor().decompress(&compressed).unwrap(),
+            expected
+        );
+    }
+}
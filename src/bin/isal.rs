@@ -0,0 +1,699 @@
+//! `isal`: command-line access to the ISA-L checksums, igzip and erasure
+//! coding.
+//!
+//! ```text
+//! isal crc <variant> [FILE...]
+//! isal gzip [-0|-1|-2|-3] [-T THREADS] [-c] [-f] [FILE...]
+//! isal gunzip [-c] [-f] [FILE...]
+//! isal ec encode -k K -p P [-o DIR] FILE
+//! isal ec decode [-o OUT] [-f] MANIFEST
+//! ```
+//!
+//! `gzip` compresses the input in independent blocks, one gzip member each,
+//! so `-T` can spread them across threads; any gzip reader accepts the
+//! concatenation. `ec encode` stripes FILE across `K + P` shard files in
+//! `UNIT`-byte units and writes a text manifest next to them; `ec decode`
+//! verifies each shard's CRC64 and rebuilds the file from any `K` good ones.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use isa_l_rust::crc;
+use isa_l_rust::erasure::ErasureCoder;
+use isa_l_rust::error::InflateError;
+use isa_l_rust::igzip::{Compressor, Decompressor};
+use isa_l_rust::sys::{IGZIP_GZIP, ISAL_INFLATE_GZIP};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+const USAGE: &str = "\
+usage: isal crc <variant> [FILE...]
+       isal gzip [-0|-1|-2|-3] [-T THREADS] [-c] [-f] [FILE...]
+       isal gunzip [-c] [-f] [FILE...]
+       isal ec encode -k K -p P [-o DIR] FILE
+       isal ec decode [-o OUT] [-f] MANIFEST
+
+CRC variants: crc16_t10dif crc32_ieee crc32_gzip_refl crc32_iscsi
+              crc64_{ecma,iso,jones,rocksoft}_{refl,norm}
+FILE may be '-' (or omitted) for stdin.";
+
+/// Uncompressed bytes per gzip member.
+const GZIP_BLOCK: usize = 4 << 20;
+/// Input and output buffer size for `gunzip`.
+const GUNZIP_BUF: usize = 256 << 10;
+/// Bytes of each shard per stripe.
+const EC_UNIT: usize = 1 << 20;
+const MANIFEST_MAGIC: &str = "# isal ec manifest v1";
+
+/// Bad command line; reported with the usage text.
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for UsageError {}
+
+fn usage<T>(msg: impl Into<String>) -> Result<T> {
+    Err(Box::new(UsageError(msg.into())))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("isal: {e}");
+        if e.is::<UsageError>() {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let (cmd, rest) = match args.split_first() {
+        Some((cmd, rest)) => (cmd.as_str(), rest),
+        None => return usage("missing command"),
+    };
+    match cmd {
+        "crc" => cmd_crc(rest),
+        "gzip" => cmd_gzip(rest),
+        "gunzip" => cmd_gunzip(rest),
+        "ec" => match rest.split_first() {
+            Some((sub, rest)) if sub == "encode" => cmd_ec_encode(rest),
+            Some((sub, rest)) if sub == "decode" => cmd_ec_decode(rest),
+            _ => usage("expected 'ec encode' or 'ec decode'"),
+        },
+        "-h" | "--help" | "help" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        "-V" | "--version" => {
+            println!(
+                "isal {} (ISA-L {})",
+                env!("CARGO_PKG_VERSION"),
+                isa_l_rust::version_str()
+            );
+            Ok(())
+        }
+        other => usage(format!("unknown command '{other}'")),
+    }
+}
+
+/// Options and positional arguments of one subcommand.
+#[derive(Default)]
+struct Opts {
+    flags: Vec<char>,
+    values: Vec<(char, String)>,
+    files: Vec<String>,
+}
+
+impl Opts {
+    /// Splits `args` into single-letter flags, `-X VALUE` options for the
+    /// letters in `with_value` and positionals. Digits are flags, so `-3`
+    /// parses as a level.
+    fn parse(args: &[String], flags: &str, with_value: &str) -> Result<Opts> {
+        let mut opts = Opts::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                opts.files.extend(iter.by_ref().cloned());
+                break;
+            }
+            let letters = match arg.strip_prefix('-') {
+                Some(letters) if !letters.is_empty() => letters,
+                _ => {
+                    opts.files.push(arg.clone());
+                    continue;
+                }
+            };
+            for (i, c) in letters.char_indices() {
+                if with_value.contains(c) {
+                    let inline = &letters[i + c.len_utf8()..];
+                    let value = if inline.is_empty() {
+                        match iter.next() {
+                            Some(v) => v.clone(),
+                            None => return usage(format!("-{c} needs a value")),
+                        }
+                    } else {
+                        inline.to_string()
+                    };
+                    opts.values.push((c, value));
+                    break;
+                } else if flags.contains(c) {
+                    opts.flags.push(c);
+                } else {
+                    return usage(format!("unknown option -{c}"));
+                }
+            }
+        }
+        Ok(opts)
+    }
+
+    fn flag(&self, c: char) -> bool {
+        self.flags.contains(&c)
+    }
+
+    fn number(&self, c: char) -> Result<Option<usize>> {
+        match self.values.iter().rev().find(|(k, _)| *k == c) {
+            Some((_, v)) => match v.parse() {
+                Ok(n) => Ok(Some(n)),
+                Err(_) => usage(format!("-{c} expects a number, got '{v}'")),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn value(&self, c: char) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == c)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Positionals, with stdin standing in when there are none.
+    fn inputs(&self) -> Vec<String> {
+        if self.files.is_empty() {
+            vec!["-".to_string()]
+        } else {
+            self.files.clone()
+        }
+    }
+}
+
+fn open_input(path: &str) -> Result<Box<dyn Read>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        let file = File::open(path).map_err(|e| format!("{path}: {e}"))?;
+        Ok(Box::new(file))
+    }
+}
+
+/// Creates `path`, refusing to replace an existing file unless `force`.
+fn create_output(path: &Path, force: bool) -> Result<BufWriter<File>> {
+    if !force && path.exists() {
+        return Err(format!("{} already exists (use -f to overwrite)", path.display()).into());
+    }
+    let file = File::create(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Fills `buf` from `r` unless EOF comes first; returns the bytes read.
+fn read_full(r: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+enum CrcState {
+    U16(fn(u16, &[u8]) -> u16, u16),
+    U32(fn(u32, &[u8]) -> u32, u32),
+    U64(fn(u64, &[u8]) -> u64, u64),
+    /// CRC-32C, which ISA-L leaves unseeded and uninverted.
+    Iscsi(u32),
+}
+
+impl CrcState {
+    fn new(variant: &str) -> Option<CrcState> {
+        Some(match variant {
+            "crc16_t10dif" => CrcState::U16(crc::crc16_t10dif, 0),
+            "crc32_ieee" => CrcState::U32(crc::crc32_ieee, 0),
+            "crc32_gzip_refl" => CrcState::U32(crc::crc32_gzip_refl, 0),
+            "crc32_iscsi" => CrcState::Iscsi(!0),
+            "crc64_ecma_refl" => CrcState::U64(crc::crc64_ecma_refl, 0),
+            "crc64_ecma_norm" => CrcState::U64(crc::crc64_ecma_norm, 0),
+            "crc64_iso_refl" => CrcState::U64(crc::crc64_iso_refl, 0),
+            "crc64_iso_norm" => CrcState::U64(crc::crc64_iso_norm, 0),
+            "crc64_jones_refl" => CrcState::U64(crc::crc64_jones_refl, 0),
+            "crc64_jones_norm" => CrcState::U64(crc::crc64_jones_norm, 0),
+            "crc64_rocksoft_refl" => CrcState::U64(crc::crc64_rocksoft_refl, 0),
+            "crc64_rocksoft_norm" => CrcState::U64(crc::crc64_rocksoft_norm, 0),
+            _ => return None,
+        })
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            CrcState::U16(f, crc) => *crc = f(*crc, data),
+            CrcState::U32(f, crc) => *crc = f(*crc, data),
+            CrcState::U64(f, crc) => *crc = f(*crc, data),
+            CrcState::Iscsi(crc) => *crc = crc::crc32_iscsi(*crc, data),
+        }
+    }
+
+    fn hex(&self) -> String {
+        match *self {
+            CrcState::U16(_, crc) => format!("{crc:04x}"),
+            CrcState::U32(_, crc) => format!("{crc:08x}"),
+            CrcState::U64(_, crc) => format!("{crc:016x}"),
+            CrcState::Iscsi(crc) => format!("{:08x}", !crc),
+        }
+    }
+}
+
+fn cmd_crc(args: &[String]) -> Result<()> {
+    let opts = Opts::parse(args, "", "")?;
+    let (variant, files) = match opts.files.split_first() {
+        Some((variant, files)) => (variant.as_str(), files),
+        None => return usage("missing CRC variant"),
+    };
+    if CrcState::new(variant).is_none() {
+        return usage(format!("unknown CRC variant '{variant}'"));
+    }
+    let files = if files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        files.to_vec()
+    };
+
+    let mut buf = vec![0u8; 1 << 20];
+    for path in &files {
+        let mut state = CrcState::new(variant).unwrap();
+        let mut input = open_input(path)?;
+        loop {
+            let n = read_full(&mut *input, &mut buf).map_err(|e| format!("{path}: {e}"))?;
+            if n == 0 {
+                break;
+            }
+            state.update(&buf[..n]);
+        }
+        println!("{}  {path}", state.hex());
+    }
+    Ok(())
+}
+
+fn cmd_gzip(args: &[String]) -> Result<()> {
+    let opts = Opts::parse(args, "0123cf", "T")?;
+    let level = opts
+        .flags
+        .iter()
+        .rev()
+        .find_map(|c| c.to_digit(10))
+        .unwrap_or(2);
+    let threads = opts.number('T')?.unwrap_or(1).max(1);
+    let mut compressors = (0..threads)
+        .map(|_| {
+            let mut c = Compressor::new(level)?;
+            c.set_format(IGZIP_GZIP);
+            Ok(c)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for path in opts.inputs() {
+        let mut input = open_input(&path)?;
+        let mut output: Box<dyn Write> = if path == "-" || opts.flag('c') {
+            Box::new(io::stdout().lock())
+        } else {
+            Box::new(create_output(
+                Path::new(&format!("{path}.gz")),
+                opts.flag('f'),
+            )?)
+        };
+        gzip_stream(&mut *input, &mut *output, &mut compressors)
+            .map_err(|e| format!("{path}: {e}"))?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Compresses `input` in `GZIP_BLOCK` pieces, one gzip member each, handing
+/// one block to each compressor per round.
+fn gzip_stream(
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    compressors: &mut [Compressor],
+) -> Result<()> {
+    let mut blocks = vec![vec![0u8; GZIP_BLOCK]; compressors.len()];
+    let mut first = true;
+    loop {
+        let mut lens = Vec::with_capacity(blocks.len());
+        for block in &mut blocks {
+            let n = read_full(input, block)?;
+            if n == 0 {
+                break;
+            }
+            lens.push(n);
+            if n < GZIP_BLOCK {
+                break;
+            }
+        }
+        if lens.is_empty() {
+            // An empty input still gets one (empty) member.
+            if first {
+                output.write_all(&compressors[0].compress(&[])?)?;
+            }
+            return Ok(());
+        }
+        first = false;
+
+        let members = std::thread::scope(|s| {
+            let handles: Vec<_> = compressors
+                .iter_mut()
+                .zip(&blocks)
+                .zip(&lens)
+                .map(|((c, block), &n)| s.spawn(move || c.compress(&block[..n])))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("compression thread panicked"))
+                .collect::<std::result::Result<Vec<_>, _>>()
+        })?;
+        for member in members {
+            output.write_all(&member)?;
+        }
+        if lens.len() < blocks.len() || *lens.last().unwrap() < GZIP_BLOCK {
+            return Ok(());
+        }
+    }
+}
+
+fn cmd_gunzip(args: &[String]) -> Result<()> {
+    let opts = Opts::parse(args, "cf", "")?;
+    let mut decompressor = Decompressor::new();
+    decompressor.set_format(ISAL_INFLATE_GZIP);
+
+    for path in opts.inputs() {
+        let mut input = open_input(&path)?;
+        let mut output: Box<dyn Write> = if path == "-" || opts.flag('c') {
+            Box::new(io::stdout().lock())
+        } else {
+            let target = match path.strip_suffix(".gz") {
+                Some(stem) if !stem.is_empty() => stem,
+                _ => return Err(format!("{path}: unknown suffix (expected .gz)").into()),
+            };
+            Box::new(create_output(Path::new(target), opts.flag('f'))?)
+        };
+        gunzip_stream(&mut *input, &mut *output, &mut decompressor)
+            .map_err(|e| format!("{path}: {e}"))?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Decompresses every gzip member in `input` through fixed-size buffers;
+/// concatenated members decompress to the concatenation of their data.
+fn gunzip_stream(
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    decompressor: &mut Decompressor,
+) -> Result<()> {
+    let mut compressed = vec![0u8; GUNZIP_BUF];
+    let mut out = vec![0u8; GUNZIP_BUF];
+    let (mut pos, mut end) = (0, 0);
+    let mut eof = false;
+    let mut in_member = false;
+    loop {
+        if pos == end && !eof {
+            end = read_full(input, &mut compressed)?;
+            pos = 0;
+            eof = end == 0;
+        }
+        if !in_member {
+            if pos == end {
+                return Ok(());
+            }
+            decompressor.begin();
+            in_member = true;
+        }
+        let (consumed, produced) = decompressor.decompress_step(&compressed[pos..end], &mut out)?;
+        pos += consumed;
+        output.write_all(&out[..produced])?;
+        if decompressor.is_finished() {
+            in_member = false;
+        } else if eof && produced == 0 {
+            return Err(InflateError::EndOfInput.into());
+        }
+    }
+}
+
+/// Contents of an `ec encode` manifest.
+struct Manifest {
+    name: String,
+    length: u64,
+    k: usize,
+    p: usize,
+    unit: usize,
+    /// `(file name, CRC64-ECMA)` per shard, data shards first.
+    shards: Vec<(String, u64)>,
+}
+
+impl Manifest {
+    fn write(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "{MANIFEST_MAGIC}")?;
+        writeln!(w, "name={}", self.name)?;
+        writeln!(w, "length={}", self.length)?;
+        writeln!(w, "k={}", self.k)?;
+        writeln!(w, "p={}", self.p)?;
+        writeln!(w, "unit={}", self.unit)?;
+        for (i, (file, crc)) in self.shards.iter().enumerate() {
+            writeln!(w, "shard={i} {crc:016x} {file}")?;
+        }
+        Ok(())
+    }
+
+    /// Bytes in each shard file.
+    fn shard_len(&self) -> u64 {
+        self.length.div_ceil((self.k * self.unit) as u64) * self.unit as u64
+    }
+
+    fn read(path: &Path) -> Result<Manifest> {
+        let bad = |what: &str| format!("{}: malformed manifest ({what})", path.display());
+        let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+        if lines.next().transpose()?.as_deref() != Some(MANIFEST_MAGIC) {
+            return Err(bad("missing header").into());
+        }
+
+        let mut m = Manifest {
+            name: String::new(),
+            length: 0,
+            k: 0,
+            p: 0,
+            unit: 0,
+            shards: Vec::new(),
+        };
+        for line in lines {
+            let line = line?;
+            let (key, value) = match line.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            let number = |v: &str| v.parse::<u64>().map_err(|_| bad(key));
+            match key {
+                "name" => m.name = value.to_string(),
+                "length" => m.length = number(value)?,
+                "k" => m.k = number(value)? as usize,
+                "p" => m.p = number(value)? as usize,
+                "unit" => m.unit = number(value)? as usize,
+                "shard" => {
+                    let mut parts = value.splitn(3, ' ');
+                    let index = parts.next().and_then(|i| i.parse::<usize>().ok());
+                    let crc = parts.next().and_then(|c| u64::from_str_radix(c, 16).ok());
+                    match (index, crc, parts.next()) {
+                        (Some(i), Some(crc), Some(file)) if i == m.shards.len() => {
+                            m.shards.push((file.to_string(), crc))
+                        }
+                        _ => return Err(bad("shard line").into()),
+                    }
+                }
+                _ => {}
+            }
+        }
+        if m.name.is_empty() || m.k == 0 || m.p == 0 || m.unit == 0 {
+            return Err(bad("missing field").into());
+        }
+        if m.k > 255 || m.p > 255 || m.shards.len() != m.k + m.p {
+            return Err(bad("shard count").into());
+        }
+        // `unit` sizes the stripe buffers, so only the value `ec encode`
+        // writes is accepted.
+        if m.unit != EC_UNIT {
+            return Err(bad("unit").into());
+        }
+        if m.length
+            .div_ceil((m.k * m.unit) as u64)
+            .checked_mul(m.unit as u64)
+            .is_none()
+        {
+            return Err(bad("length").into());
+        }
+        // Names are resolved against the manifest's directory, so a path
+        // could point anywhere.
+        let plain = |name: &str| {
+            !name.contains(['/', '\\']) && Path::new(name).file_name() == Some(name.as_ref())
+        };
+        if !plain(&m.name) || !m.shards.iter().all(|(file, _)| plain(file)) {
+            return Err(bad("file name is a path").into());
+        }
+        Ok(m)
+    }
+}
+
+fn cmd_ec_encode(args: &[String]) -> Result<()> {
+    let opts = Opts::parse(args, "", "kpo")?;
+    let (k, p) = match (opts.number('k')?, opts.number('p')?) {
+        (Some(k), Some(p)) => (k, p),
+        _ => return usage("ec encode needs -k and -p"),
+    };
+    let path = match opts.files.as_slice() {
+        [path] if path != "-" => Path::new(path),
+        _ => return usage("ec encode takes exactly one FILE"),
+    };
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => return usage(format!("{}: not a file", path.display())),
+    };
+    let dir = match opts.value('o') {
+        Some(dir) => PathBuf::from(dir),
+        None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    let coder = ErasureCoder::new(k, p)?;
+
+    let mut input = open_input(&path.to_string_lossy())?;
+    let shard_names: Vec<String> = (0..k + p).map(|i| format!("{name}.{i}.shard")).collect();
+    let mut outputs = shard_names
+        .iter()
+        .map(|s| create_output(&dir.join(s), true))
+        .collect::<Result<Vec<_>>>()?;
+    let mut crcs = vec![0u64; k + p];
+    let mut stripe = vec![0u8; (k + p) * EC_UNIT];
+    let mut length = 0u64;
+
+    loop {
+        let (data, parity) = stripe.split_at_mut(k * EC_UNIT);
+        let n = read_full(&mut *input, data)?;
+        if n == 0 {
+            break;
+        }
+        length += n as u64;
+        data[n..].iter_mut().for_each(|b| *b = 0);
+
+        let data: Vec<&[u8]> = data.chunks(EC_UNIT).collect();
+        let mut parity: Vec<&mut [u8]> = parity.chunks_mut(EC_UNIT).collect();
+        coder.encode(&data, &mut parity)?;
+
+        for ((unit, out), crc) in stripe.chunks(EC_UNIT).zip(&mut outputs).zip(&mut crcs) {
+            out.write_all(unit)?;
+            *crc = crc::crc64_ecma_refl(*crc, unit);
+        }
+        if n < k * EC_UNIT {
+            break;
+        }
+    }
+    for out in &mut outputs {
+        out.flush()?;
+    }
+
+    let manifest = Manifest {
+        name: name.clone(),
+        length,
+        k,
+        p,
+        unit: EC_UNIT,
+        shards: shard_names.into_iter().zip(crcs).collect(),
+    };
+    let manifest_path = dir.join(format!("{name}.manifest"));
+    let mut out = create_output(&manifest_path, true)?;
+    manifest.write(&mut out)?;
+    out.flush()?;
+    eprintln!(
+        "isal: wrote {} shards and {}",
+        k + p,
+        manifest_path.display()
+    );
+    Ok(())
+}
+
+/// Whether the shard at `path` exists and matches its recorded CRC64.
+fn shard_ok(path: &Path, expected: u64, unit: usize, len: u64) -> bool {
+    let mut file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+    if file.metadata().map(|md| md.len()).ok() != Some(len) {
+        return false;
+    }
+    let mut buf = vec![0u8; unit];
+    let mut crc = 0u64;
+    loop {
+        match read_full(&mut file, &mut buf) {
+            Ok(0) => return crc == expected,
+            Ok(n) => crc = crc::crc64_ecma_refl(crc, &buf[..n]),
+            Err(_) => return false,
+        }
+    }
+}
+
+fn cmd_ec_decode(args: &[String]) -> Result<()> {
+    let opts = Opts::parse(args, "f", "o")?;
+    let manifest_path = match opts.files.as_slice() {
+        [path] => Path::new(path),
+        _ => return usage("ec decode takes exactly one MANIFEST"),
+    };
+    let m = Manifest::read(manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+    let coder = ErasureCoder::new(m.k, m.p)?;
+
+    let paths: Vec<PathBuf> = m.shards.iter().map(|(f, _)| dir.join(f)).collect();
+    let mut erasures = Vec::new();
+    let mut inputs = Vec::with_capacity(paths.len());
+    for (i, (path, (_, crc))) in paths.iter().zip(&m.shards).enumerate() {
+        if shard_ok(path, *crc, m.unit, m.shard_len()) {
+            inputs.push(Some(BufReader::new(File::open(path)?)));
+        } else {
+            eprintln!("isal: {}: missing or corrupt, rebuilding", path.display());
+            erasures.push(i);
+            inputs.push(None);
+        }
+    }
+    if erasures.len() > m.p {
+        return Err(format!(
+            "only {} of {} shards usable, need {}",
+            m.k + m.p - erasures.len(),
+            m.k + m.p,
+            m.k
+        )
+        .into());
+    }
+
+    let mut output: Box<dyn Write> = match opts.value('o') {
+        Some("-") => Box::new(io::stdout().lock()),
+        Some(out) => Box::new(create_output(Path::new(out), opts.flag('f'))?),
+        None => Box::new(create_output(&dir.join(&m.name), opts.flag('f'))?),
+    };
+
+    let mut stripe = vec![0u8; (m.k + m.p) * m.unit];
+    let mut remaining = m.length;
+    while remaining > 0 {
+        for (unit, input) in stripe.chunks_mut(m.unit).zip(&mut inputs) {
+            if let Some(input) = input {
+                input.read_exact(unit)?;
+            }
+        }
+        if !erasures.is_empty() {
+            let mut shards: Vec<&mut [u8]> = stripe.chunks_mut(m.unit).collect();
+            coder.reconstruct(&mut shards, &erasures)?;
+        }
+        let take = remaining.min((m.k * m.unit) as u64) as usize;
+        output.write_all(&stripe[..take])?;
+        remaining -= take as u64;
+    }
+    output.flush()?;
+    Ok(())
+}
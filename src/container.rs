@@ -0,0 +1,657 @@
+//! Self-describing erasure-coded container format.
+//!
+//! A container is a header followed by the payload in stripes. Each stripe
+//! holds `k` data units and `p` parity units of `stripe_size` bytes, unit
+//! `i` of every stripe belonging to shard `i`; the last stripe is
+//! zero-padded. Every stripe is followed by a CRC64 of each of its units and
+//! a CRC64 of those, so damage is located (and repaired) stripe by stripe.
+//! All integers are little-endian:
+//!
+//! ```text
+//! offset  size        field
+//! 0       7           magic "ISALEC\0"
+//! 7       1           version (1)
+//! 8       1           k
+//! 9       1           p
+//! 10      1           matrix (0 = Cauchy, 1 = Vandermonde)
+//! 11      1           reserved (0)
+//! 12      4           stripe_size
+//! 16      8           original length
+//! 24      8 * (k+p)   CRC64-ECMA (reflected) of each shard's units, in order
+//! ..      8           CRC64-ECMA of all preceding header bytes
+//!
+//! then per stripe:
+//! 0       (k+p) * stripe_size   the units
+//! ..      8 * (k+p)   CRC64-ECMA of each unit
+//! ..      8           CRC64-ECMA of the unit CRCs
+//! ```
+//!
+//! [`ContainerWriter`] streams input through `ec_encode_data` one stripe at
+//! a time and patches the shard CRCs into the header on
+//! [`finish`](ContainerWriter::finish). [`ContainerReader`] checks every
+//! unit against its CRC when opened and rebuilds the ones that fail, or
+//! that are cut short, while reading. A stripe whose own CRC table is
+//! unreadable falls back to the whole-shard CRCs.
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::crc::crc64_ecma_refl;
+use crate::erasure::{ErasureCoder, MatrixKind};
+use crate::error::ContainerError;
+
+/// First bytes of every container.
+pub const MAGIC: [u8; 7] = *b"ISALEC\0";
+
+/// Format version written by [`ContainerWriter`].
+pub const VERSION: u8 = 1;
+
+/// Stripe size used by [`ContainerWriter::new`] callers that have no
+/// better choice.
+pub const DEFAULT_STRIPE_SIZE: u32 = 1 << 20;
+
+/// Largest stripe size accepted, bounding the stripe buffer readers
+/// allocate from an untrusted header.
+pub const MAX_STRIPE_SIZE: u32 = 64 << 20;
+
+/// Header bytes before the shard CRCs.
+const FIXED_LEN: usize = 24;
+
+/// Decoded container header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub k: usize,
+    pub p: usize,
+    pub matrix: MatrixKind,
+    /// Bytes of each shard per stripe.
+    pub stripe_size: u32,
+    /// Length of the original payload.
+    pub length: u64,
+    /// CRC64 of each shard, data shards first.
+    pub shard_crcs: Vec<u64>,
+}
+
+impl Header {
+    /// Size of the encoded header in bytes.
+    pub fn encoded_len(&self) -> usize {
+        FIXED_LEN + 8 * (self.k + self.p) + 8
+    }
+
+    /// Number of stripes following the header.
+    pub fn stripes(&self) -> u64 {
+        let per_stripe = self.k as u64 * self.stripe_size as u64;
+        self.length.div_ceil(per_stripe)
+    }
+
+    /// Bytes of the unit CRC table after each stripe.
+    fn table_len(&self) -> usize {
+        8 * (self.k + self.p + 1)
+    }
+
+    /// Bytes of one stripe including its CRC table.
+    fn stripe_len(&self) -> u64 {
+        (self.k + self.p) as u64 * self.stripe_size as u64 + self.table_len() as u64
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.encoded_len());
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.push(self.k as u8);
+        out.push(self.p as u8);
+        out.push(match self.matrix {
+            MatrixKind::Cauchy => 0,
+            MatrixKind::Vandermonde => 1,
+        });
+        out.push(0);
+        out.extend_from_slice(&self.stripe_size.to_le_bytes());
+        out.extend_from_slice(&self.length.to_le_bytes());
+        for crc in &self.shard_crcs {
+            out.extend_from_slice(&crc.to_le_bytes());
+        }
+        let crc = crc64_ecma_refl(0, &out);
+        out.extend_from_slice(&crc.to_le_bytes());
+        out
+    }
+
+    fn read(r: &mut impl Read) -> Result<Header, ContainerError> {
+        let mut fixed = [0u8; FIXED_LEN];
+        r.read_exact(&mut fixed)?;
+        if fixed[..7] != MAGIC {
+            return Err(ContainerError::BadMagic);
+        }
+        if fixed[7] != VERSION {
+            return Err(ContainerError::UnsupportedVersion(fixed[7]));
+        }
+
+        let (k, p) = (fixed[8] as usize, fixed[9] as usize);
+        let matrix = match fixed[10] {
+            0 => MatrixKind::Cauchy,
+            1 => MatrixKind::Vandermonde,
+            _ => return Err(ContainerError::CorruptHeader),
+        };
+        let stripe_size = u32::from_le_bytes(fixed[12..16].try_into().unwrap());
+        let length = u64::from_le_bytes(fixed[16..24].try_into().unwrap());
+        if k == 0 || p == 0 || k + p > 255 || stripe_size == 0 || stripe_size > MAX_STRIPE_SIZE {
+            return Err(ContainerError::CorruptHeader);
+        }
+
+        let mut rest = vec![0u8; 8 * (k + p + 1)];
+        r.read_exact(&mut rest)?;
+        let words: Vec<u64> = rest
+            .chunks(8)
+            .map(|w| u64::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        let (&stored, shard_crcs) = words.split_last().unwrap();
+        let crc = crc64_ecma_refl(crc64_ecma_refl(0, &fixed), &rest[..rest.len() - 8]);
+        if crc != stored {
+            return Err(ContainerError::CorruptHeader);
+        }
+
+        Ok(Header {
+            k,
+            p,
+            matrix,
+            stripe_size,
+            length,
+            shard_crcs: shard_crcs.to_vec(),
+        })
+    }
+}
+
+/// The `i`th little-endian `u64` of `words`.
+fn word(words: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(words[8 * i..8 * i + 8].try_into().unwrap())
+}
+
+/// Fills `buf` from `r` unless EOF comes first; returns the bytes read.
+fn read_full(r: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Streams a payload into a container.
+///
+/// Bytes written are buffered until a full stripe is available, then
+/// encoded and written out. [`finish`](Self::finish) must be called to write
+/// the final stripe and the shard CRCs; a writer dropped without it leaves a
+/// container whose header CRCs do not match.
+pub struct ContainerWriter<W: Write + Seek> {
+    inner: W,
+    coder: ErasureCoder,
+    header: Header,
+    /// Stream position of the header.
+    start: u64,
+    /// One stripe: `k` data units followed by `p` parity units.
+    stripe: Vec<u8>,
+    /// Payload bytes buffered in the data units.
+    filled: usize,
+}
+
+impl<W: Write + Seek> ContainerWriter<W> {
+    /// Writes a placeholder header at the current position of `inner`.
+    ///
+    /// # Errors
+    /// [`ContainerError::InvalidStripeSize`] for a `stripe_size` of zero or
+    /// above [`MAX_STRIPE_SIZE`], [`ContainerError::Ec`] for invalid `k` /
+    /// `p`.
+    pub fn new(
+        mut inner: W,
+        k: usize,
+        p: usize,
+        matrix: MatrixKind,
+        stripe_size: u32,
+    ) -> Result<Self, ContainerError> {
+        if stripe_size == 0 || stripe_size > MAX_STRIPE_SIZE {
+            return Err(ContainerError::InvalidStripeSize);
+        }
+        let coder = ErasureCoder::with_matrix(k, p, matrix)?;
+        let header = Header {
+            k,
+            p,
+            matrix,
+            stripe_size,
+            length: 0,
+            shard_crcs: vec![0; k + p],
+        };
+        let start = inner.stream_position()?;
+        inner.write_all(&header.encode())?;
+
+        Ok(ContainerWriter {
+            inner,
+            coder,
+            header,
+            start,
+            stripe: vec![0u8; (k + p) * stripe_size as usize],
+            filled: 0,
+        })
+    }
+
+    /// The header as it stands; CRCs cover only the stripes written so far.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn write_stripe(&mut self) -> io::Result<()> {
+        let unit = self.header.stripe_size as usize;
+        let (data, parity) = self.stripe.split_at_mut(self.header.k * unit);
+        data[self.filled..].iter_mut().for_each(|b| *b = 0);
+
+        let data: Vec<&[u8]> = data.chunks(unit).collect();
+        let mut parity: Vec<&mut [u8]> = parity.chunks_mut(unit).collect();
+        self.coder
+            .encode(&data, &mut parity)
+            .expect("stripe buffers match the coder");
+
+        let mut table = Vec::with_capacity(self.header.table_len());
+        for (u, crc) in self.stripe.chunks(unit).zip(&mut self.header.shard_crcs) {
+            *crc = crc64_ecma_refl(*crc, u);
+            table.extend_from_slice(&crc64_ecma_refl(0, u).to_le_bytes());
+        }
+        table.extend_from_slice(&crc64_ecma_refl(0, &table).to_le_bytes());
+        self.inner.write_all(&self.stripe)?;
+        self.inner.write_all(&table)?;
+        self.filled = 0;
+        Ok(())
+    }
+
+    /// Writes the final partial stripe and the completed header, and
+    /// returns the inner writer positioned after the container.
+    pub fn finish(mut self) -> Result<W, ContainerError> {
+        if self.filled > 0 {
+            self.write_stripe()?;
+        }
+        let end = self.inner.stream_position()?;
+        self.inner.seek(SeekFrom::Start(self.start))?;
+        self.inner.write_all(&self.header.encode())?;
+        self.inner.seek(SeekFrom::Start(end))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write + Seek> Write for ContainerWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let capacity = self.header.k * self.header.stripe_size as usize;
+        let n = buf.len().min(capacity - self.filled);
+        self.stripe[self.filled..self.filled + n].copy_from_slice(&buf[..n]);
+        self.filled += n;
+        self.header.length += n as u64;
+        if self.filled == capacity {
+            self.write_stripe()?;
+        }
+        Ok(n)
+    }
+
+    /// Flushes the inner writer. A partially filled stripe stays buffered
+    /// until it fills or [`finish`](ContainerWriter::finish) is called.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reads the payload back out of a container, rebuilding damaged shards.
+pub struct ContainerReader<R: Read + Seek> {
+    inner: R,
+    coder: ErasureCoder,
+    header: Header,
+    /// Shards with a unit that failed its CRC or was cut short, in index
+    /// order.
+    damaged: Vec<usize>,
+    /// Damaged units of each stripe that has any.
+    stripe_damage: BTreeMap<u64, Vec<usize>>,
+    /// Index of the next stripe to load.
+    next_stripe: u64,
+    stripe: Vec<u8>,
+    /// Scratch for skipping each stripe's CRC table.
+    table: Vec<u8>,
+    /// Read position within the decoded payload bytes of `stripe`.
+    pos: usize,
+    /// Decoded payload bytes in `stripe`.
+    avail: usize,
+    /// Payload bytes not yet loaded into `stripe`.
+    remaining: u64,
+}
+
+impl<R: Read + Seek> ContainerReader<R> {
+    /// Parses the header at the current position of `inner` and checks every
+    /// unit against its CRC, which reads the whole container once.
+    ///
+    /// # Errors
+    /// [`ContainerError::Unrecoverable`] if more than `p` units of a stripe
+    /// are damaged; [`ContainerError::CorruptHeader`] if the header
+    /// describes more stripes than the stream could hold even with `p`
+    /// units of the last one missing; header errors as described on
+    /// [`ContainerError`].
+    pub fn open(mut inner: R) -> Result<Self, ContainerError> {
+        let header = Header::read(&mut inner)?;
+        let data_start = inner.stream_position()?;
+        let stream_end = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(data_start))?;
+
+        // Check the size the header claims before allocating anything
+        // based on it.
+        let stripes = header.stripes();
+        let unit = header.stripe_size as usize;
+        let n = header.k + header.p;
+        let table_len = header.table_len();
+        let missable = header.p as u64 * unit as u64 + table_len as u64;
+        let claimed = stripes
+            .checked_mul(header.stripe_len())
+            .ok_or(ContainerError::CorruptHeader)?;
+        if claimed.saturating_sub(missable) > stream_end.saturating_sub(data_start) {
+            return Err(ContainerError::CorruptHeader);
+        }
+
+        let coder = ErasureCoder::with_matrix(header.k, header.p, header.matrix)?;
+        let mut stripe = vec![0u8; if stripes > 0 { n * unit } else { 0 }];
+        let mut table = vec![0u8; table_len];
+
+        let mut shard_crcs = vec![0u64; n];
+        let mut short = vec![false; n];
+        let mut stripe_damage = BTreeMap::new();
+        let mut unchecked = Vec::new();
+        for s in 0..stripes {
+            let got = read_full(&mut inner, &mut stripe)?;
+            stripe[got..].iter_mut().for_each(|b| *b = 0);
+            let table_ok = got == stripe.len()
+                && read_full(&mut inner, &mut table)? == table_len
+                && crc64_ecma_refl(0, &table[..table_len - 8]) == word(&table, n);
+
+            let mut bad = Vec::new();
+            for (i, u) in stripe.chunks(unit).enumerate() {
+                short[i] |= got < (i + 1) * unit;
+                shard_crcs[i] = crc64_ecma_refl(shard_crcs[i], u);
+                if table_ok && crc64_ecma_refl(0, u) != word(&table, i) {
+                    bad.push(i);
+                }
+            }
+            if !table_ok {
+                unchecked.push(s);
+            } else if !bad.is_empty() {
+                stripe_damage.insert(s, bad);
+            }
+        }
+
+        // Stripes without a usable CRC table rebuild every shard that fails
+        // its whole-shard CRC.
+        let shard_bad: Vec<usize> = (0..n)
+            .filter(|&i| short[i] || shard_crcs[i] != header.shard_crcs[i])
+            .collect();
+        if !shard_bad.is_empty() {
+            for s in unchecked {
+                stripe_damage.insert(s, shard_bad.clone());
+            }
+        }
+
+        if let Some(worst) = stripe_damage.values().map(Vec::len).max() {
+            if worst > header.p {
+                return Err(ContainerError::Unrecoverable {
+                    damaged: worst,
+                    parity: header.p,
+                });
+            }
+        }
+        let mut damaged: Vec<usize> = stripe_damage.values().flatten().copied().collect();
+        damaged.sort_unstable();
+        damaged.dedup();
+        inner.seek(SeekFrom::Start(data_start))?;
+
+        Ok(ContainerReader {
+            inner,
+            coder,
+            remaining: header.length,
+            header,
+            damaged,
+            stripe_damage,
+            next_stripe: 0,
+            stripe,
+            table,
+            pos: 0,
+            avail: 0,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Shards with at least one unit that failed verification; each is
+    /// rebuilt only in the stripes where it is damaged.
+    pub fn damaged_shards(&self) -> &[usize] {
+        &self.damaged
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn load_stripe(&mut self) -> io::Result<()> {
+        let got = read_full(&mut self.inner, &mut self.stripe)?;
+        self.stripe[got..].iter_mut().for_each(|b| *b = 0);
+        // The unit CRCs were checked by `open`.
+        read_full(&mut self.inner, &mut self.table)?;
+        if let Some(erasures) = self.stripe_damage.get(&self.next_stripe) {
+            let mut shards: Vec<&mut [u8]> = self
+                .stripe
+                .chunks_mut(self.header.stripe_size as usize)
+                .collect();
+            self.coder
+                .reconstruct(&mut shards, erasures)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+        self.next_stripe += 1;
+        let capacity = self.header.k * self.header.stripe_size as usize;
+        self.avail = self.remaining.min(capacity as u64) as usize;
+        self.remaining -= self.avail as u64;
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for ContainerReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.avail {
+            if self.remaining == 0 {
+                return Ok(0);
+            }
+            self.load_stripe()?;
+        }
+        let n = buf.len().min(self.avail - self.pos);
+        buf[..n].copy_from_slice(&self.stripe[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 37 + (i >> 9)) as u8).collect()
+    }
+
+    fn build(data: &[u8], k: usize, p: usize, matrix: MatrixKind, stripe: u32) -> Vec<u8> {
+        let mut w = ContainerWriter::new(Cursor::new(Vec::new()), k, p, matrix, stripe).unwrap();
+        for chunk in data.chunks(1000) {
+            w.write_all(chunk).unwrap();
+        }
+        w.finish().unwrap().into_inner()
+    }
+
+    fn read_back(container: Vec<u8>) -> Result<(Vec<u8>, Vec<usize>), ContainerError> {
+        let mut r = ContainerReader::open(Cursor::new(container))?;
+        let mut out = Vec::new();
+        r.read_to_end(&mut out)?;
+        Ok((out, r.damaged_shards().to_vec()))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for (len, matrix) in [
+            (0, MatrixKind::Cauchy),
+            (1, MatrixKind::Cauchy),
+            (4096 * 4, MatrixKind::Cauchy),
+            (100_003, MatrixKind::Vandermonde),
+        ] {
+            let data = payload(len);
+            let container = build(&data, 4, 2, matrix, 4096);
+            let header = Header::read(&mut Cursor::new(&container)).unwrap();
+            assert_eq!(header.length, len as u64);
+            assert_eq!(
+                container.len() as u64,
+                header.encoded_len() as u64 + header.stripes() * (6 * 4096 + 7 * 8)
+            );
+            assert_eq!(read_back(container).unwrap(), (data, vec![]));
+        }
+    }
+
+    #[test]
+    fn test_rebuilds_corrupt_shards() {
+        let data = payload(50_000);
+        let mut container = build(&data, 5, 3, MatrixKind::Cauchy, 1024);
+        let start = Header::read(&mut Cursor::new(&container))
+            .unwrap()
+            .encoded_len();
+        let unit = |stripe: usize, shard: usize| start + stripe * (8 * 1024 + 9 * 8) + shard * 1024;
+
+        // Flip a byte in data shard 1 and parity shard 6, and wipe a unit of
+        // data shard 3.
+        container[unit(2, 1) + 5] ^= 0x40;
+        container[unit(0, 6)] ^= 1;
+        container[unit(9, 3)..unit(9, 4)].fill(0);
+        assert_eq!(read_back(container).unwrap(), (data, vec![1, 3, 6]));
+    }
+
+    #[test]
+    fn test_damage_in_different_stripes() {
+        // Three shards of a p = 2 container, each damaged in its own stripe.
+        let data = payload(40_000);
+        let mut container = build(&data, 4, 2, MatrixKind::Cauchy, 1024);
+        let start = Header::read(&mut Cursor::new(&container))
+            .unwrap()
+            .encoded_len();
+        let unit = |stripe: usize, shard: usize| start + stripe * (6 * 1024 + 7 * 8) + shard * 1024;
+        container[unit(0, 0) + 1] ^= 1;
+        container[unit(3, 2) + 2] ^= 2;
+        container[unit(7, 5) + 3] ^= 4;
+        assert_eq!(read_back(container).unwrap(), (data, vec![0, 2, 5]));
+    }
+
+    #[test]
+    fn test_corrupt_crc_table() {
+        // An unreadable table falls back to the whole-shard CRCs.
+        let data = payload(20_000);
+        let mut container = build(&data, 4, 2, MatrixKind::Cauchy, 1024);
+        let start = Header::read(&mut Cursor::new(&container))
+            .unwrap()
+            .encoded_len();
+        let table = start + 6 * 1024;
+        container[table] ^= 1;
+        assert_eq!(
+            read_back(container.clone()).unwrap(),
+            (data.clone(), vec![])
+        );
+        container[start + 3 * 1024] ^= 1;
+        assert_eq!(read_back(container).unwrap(), (data, vec![3]));
+    }
+
+    #[test]
+    fn test_forged_header_sizes() {
+        let forge = |k: usize, p: usize, stripe_size: u32, length: u64| {
+            Header {
+                k,
+                p,
+                matrix: MatrixKind::Cauchy,
+                stripe_size,
+                length,
+                shard_crcs: vec![0; k + p],
+            }
+            .encode()
+        };
+        // Valid header CRCs, absurd sizes: errors rather than allocations.
+        for container in [
+            forge(200, 55, u32::MAX, 1 << 40),
+            forge(200, 55, MAX_STRIPE_SIZE, 1 << 40),
+            forge(4, 2, 1 << 20, 100 << 20),
+        ] {
+            assert!(matches!(
+                read_back(container),
+                Err(ContainerError::CorruptHeader)
+            ));
+        }
+        // Nothing to read needs no stripe buffer.
+        let empty = forge(200, 55, MAX_STRIPE_SIZE, 0);
+        assert_eq!(read_back(empty).unwrap(), (vec![], vec![]));
+
+        assert!(matches!(
+            ContainerWriter::new(
+                Cursor::new(Vec::new()),
+                2,
+                1,
+                MatrixKind::Cauchy,
+                MAX_STRIPE_SIZE + 1
+            ),
+            Err(ContainerError::InvalidStripeSize)
+        ));
+    }
+
+    #[test]
+    fn test_truncated_parity() {
+        let data = payload(20_000);
+        let mut container = build(&data, 4, 2, MatrixKind::Cauchy, 2048);
+        container.truncate(container.len() - 100);
+        assert_eq!(read_back(container).unwrap(), (data, vec![5]));
+    }
+
+    #[test]
+    fn test_unrecoverable() {
+        let data = payload(20_000);
+        let mut container = build(&data, 4, 2, MatrixKind::Cauchy, 2048);
+        let start = container.len() - 7 * 8 - 6 * 2048;
+        for shard in [0, 2, 4] {
+            container[start + shard * 2048] ^= 0xFF;
+        }
+        assert!(matches!(
+            read_back(container),
+            Err(ContainerError::Unrecoverable {
+                damaged: 3,
+                parity: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_header_errors() {
+        let mut container = build(&payload(100), 2, 1, MatrixKind::Cauchy, 64);
+        container[9] = 2;
+        assert!(matches!(
+            read_back(container.clone()),
+            Err(ContainerError::CorruptHeader)
+        ));
+        container[7] = 9;
+        assert!(matches!(
+            read_back(container.clone()),
+            Err(ContainerError::UnsupportedVersion(9))
+        ));
+        container[0] = b'X';
+        assert!(matches!(
+            read_back(container),
+            Err(ContainerError::BadMagic)
+        ));
+        assert!(matches!(
+            ContainerWriter::new(Cursor::new(Vec::new()), 2, 1, MatrixKind::Cauchy, 0),
+            Err(ContainerError::InvalidStripeSize)
+        ));
+    }
+}
@@ -0,0 +1,332 @@
+//! CPU feature detection and multi-binary dispatch introspection.
+//!
+//! ISA-L's multi-binary entry points (`ec_encode_data`, `xor_gen`, ...) pick
+//! an architecture-specific kernel on first call. The chosen pointer is a
+//! private symbol, so [`dispatched_impl`] re-derives the choice from the same
+//! CPUID rules the dispatcher applies (as of ISA-L 2.31) and names the kernel
+//! it resolves to.
+//!
+//! [`Backend`] names the erasure-coding kernel families so callers can pin
+//! one explicitly (see `ErasureCoder::with_backend`) after checking that the
+//! CPU supports it.
+
+use std::fmt;
+
+/// Instruction-set extensions relevant to ISA-L's kernels, as detected at
+/// runtime (including OS support for the wider register files).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuFeatures {
+    pub sse4_1: bool,
+    pub sse4_2: bool,
+    /// PCLMULQDQ carry-less multiply, used by the CRC folding kernels.
+    pub clmul: bool,
+    pub avx: bool,
+    pub avx2: bool,
+    /// The AVX-512 subset ISA-L requires: F, CD, BW, DQ and VL.
+    pub avx512: bool,
+    pub gfni: bool,
+    pub vpclmulqdq: bool,
+}
+
+impl fmt::Display for CpuFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (self.sse4_1, "sse4.1"),
+            (self.sse4_2, "sse4.2"),
+            (self.clmul, "clmul"),
+            (self.avx, "avx"),
+            (self.avx2, "avx2"),
+            (self.avx512, "avx512"),
+            (self.gfni, "gfni"),
+            (self.vpclmulqdq, "vpclmulqdq"),
+        ];
+        let mut any = false;
+        for name in names.iter().filter(|(on, _)| *on).map(|(_, n)| n) {
+            if any {
+                f.write_str(" ")?;
+            }
+            f.write_str(name)?;
+            any = true;
+        }
+        if !any {
+            f.write_str("none")?;
+        }
+        Ok(())
+    }
+}
+
+/// Detects the extensions available on the running CPU. All fields are
+/// `false` on non-x86 targets.
+pub fn cpu_features() -> CpuFeatures {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        CpuFeatures {
+            sse4_1: is_x86_feature_detected!("sse4.1"),
+            sse4_2: is_x86_feature_detected!("sse4.2"),
+            clmul: is_x86_feature_detected!("pclmulqdq"),
+            avx: is_x86_feature_detected!("avx"),
+            avx2: is_x86_feature_detected!("avx2"),
+            avx512: is_x86_feature_detected!("avx512f")
+                && is_x86_feature_detected!("avx512cd")
+                && is_x86_feature_detected!("avx512bw")
+                && is_x86_feature_detected!("avx512dq")
+                && is_x86_feature_detected!("avx512vl"),
+            gfni: is_x86_feature_detected!("gfni"),
+            vpclmulqdq: is_x86_feature_detected!("vpclmulqdq"),
+        }
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        CpuFeatures::default()
+    }
+}
+
+/// Erasure-coding kernel family.
+///
+/// The GFNI kernels consume tables from `ec_init_tables_gfni` (8 bytes per
+/// coefficient) rather than `ec_init_tables` (32 bytes per coefficient);
+/// `ErasureCoder` builds whichever format the backend needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Portable C, available everywhere.
+    Base,
+    Sse,
+    Avx,
+    Avx2,
+    Avx512,
+    Avx2Gfni,
+    Avx512Gfni,
+}
+
+impl Backend {
+    /// Every backend, in the dispatcher's order of preference, lowest first
+    /// (AVX-512 without GFNI still wins over AVX2 with it).
+    pub const ALL: [Backend; 7] = [
+        Backend::Base,
+        Backend::Sse,
+        Backend::Avx,
+        Backend::Avx2,
+        Backend::Avx2Gfni,
+        Backend::Avx512,
+        Backend::Avx512Gfni,
+    ];
+
+    /// Kernel name suffix, e.g. `"avx2_gfni"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Base => "base",
+            Backend::Sse => "sse",
+            Backend::Avx => "avx",
+            Backend::Avx2 => "avx2",
+            Backend::Avx512 => "avx512",
+            Backend::Avx2Gfni => "avx2_gfni",
+            Backend::Avx512Gfni => "avx512_gfni",
+        }
+    }
+
+    /// Whether the running CPU can execute this backend's kernels. Only
+    /// [`Backend::Base`] is available off x86_64.
+    pub fn is_supported(self) -> bool {
+        self.supported_by(&cpu_features())
+    }
+
+    /// The backend the `ec_encode_data` dispatcher picks on this CPU.
+    pub fn detect() -> Backend {
+        Backend::dispatched(&cpu_features())
+    }
+
+    /// Whether this backend needs `ec_init_tables_gfni` tables.
+    pub fn uses_gfni_tables(self) -> bool {
+        matches!(self, Backend::Avx2Gfni | Backend::Avx512Gfni)
+    }
+
+    fn supported_by(self, cpu: &CpuFeatures) -> bool {
+        if cfg!(not(target_arch = "x86_64")) {
+            return self == Backend::Base;
+        }
+        match self {
+            Backend::Base => true,
+            Backend::Sse => cpu.sse4_1,
+            Backend::Avx => cpu.avx,
+            Backend::Avx2 => cpu.avx2,
+            Backend::Avx512 => cpu.avx512,
+            Backend::Avx2Gfni => cpu.avx2 && cpu.gfni,
+            Backend::Avx512Gfni => cpu.avx512 && cpu.gfni,
+        }
+    }
+
+    fn dispatched(cpu: &CpuFeatures) -> Backend {
+        Backend::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|b| b.supported_by(cpu))
+            .unwrap_or(Backend::Base)
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Multi-binary entry points whose kernel choice can be queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Function {
+    /// `ec_encode_data`
+    EcEncode,
+    /// `ec_encode_data_update`
+    EcEncodeUpdate,
+    /// `gf_vect_dot_prod`
+    GfVectDotProd,
+    /// `gf_vect_mad`
+    GfVectMad,
+    /// `gf_vect_mul`
+    GfVectMul,
+    /// `xor_gen`
+    XorGen,
+    /// `pq_gen`
+    PqGen,
+}
+
+impl Function {
+    /// Name of the multi-binary entry point.
+    pub fn name(self) -> &'static str {
+        match self {
+            Function::EcEncode => "ec_encode_data",
+            Function::EcEncodeUpdate => "ec_encode_data_update",
+            Function::GfVectDotProd => "gf_vect_dot_prod",
+            Function::GfVectMad => "gf_vect_mad",
+            Function::GfVectMul => "gf_vect_mul",
+            Function::XorGen => "xor_gen",
+            Function::PqGen => "pq_gen",
+        }
+    }
+}
+
+/// Name of the kernel the dispatcher selects for `f` on this CPU, e.g.
+/// `"ec_encode_data_avx2_gfni"`.
+pub fn dispatched_impl(f: Function) -> &'static str {
+    dispatch(f, &cpu_features())
+}
+
+macro_rules! kernel {
+    ($f:expr, $suffix:literal) => {
+        match $f {
+            Function::EcEncode => concat!("ec_encode_data", $suffix),
+            Function::EcEncodeUpdate => concat!("ec_encode_data_update", $suffix),
+            Function::GfVectDotProd => concat!("gf_vect_dot_prod", $suffix),
+            Function::GfVectMad => concat!("gf_vect_mad", $suffix),
+            Function::GfVectMul => concat!("gf_vect_mul", $suffix),
+            Function::XorGen => concat!("xor_gen", $suffix),
+            Function::PqGen => concat!("pq_gen", $suffix),
+        }
+    };
+}
+
+/// The dispatcher's selection rules for x86_64; other targets run `_base`
+/// (aarch64 kernels are not bound by this crate).
+fn dispatch(f: Function, cpu: &CpuFeatures) -> &'static str {
+    if cfg!(not(target_arch = "x86_64")) {
+        return kernel!(f, "_base");
+    }
+
+    match f {
+        Function::EcEncode
+        | Function::EcEncodeUpdate
+        | Function::GfVectDotProd
+        | Function::GfVectMad => match Backend::dispatched(cpu) {
+            Backend::Base => kernel!(f, "_base"),
+            Backend::Sse => kernel!(f, "_sse"),
+            Backend::Avx => kernel!(f, "_avx"),
+            Backend::Avx2 => kernel!(f, "_avx2"),
+            Backend::Avx512 => kernel!(f, "_avx512"),
+            Backend::Avx2Gfni => kernel!(f, "_avx2_gfni"),
+            Backend::Avx512Gfni => kernel!(f, "_avx512_gfni"),
+        },
+        Function::GfVectMul => {
+            if cpu.avx {
+                kernel!(f, "_avx")
+            } else if cpu.sse4_1 {
+                kernel!(f, "_sse")
+            } else {
+                kernel!(f, "_base")
+            }
+        }
+        Function::XorGen => {
+            if cpu.avx512 {
+                kernel!(f, "_avx512")
+            } else if cpu.avx {
+                kernel!(f, "_avx")
+            } else if cpu.sse4_1 {
+                kernel!(f, "_sse")
+            } else {
+                kernel!(f, "_base")
+            }
+        }
+        Function::PqGen => {
+            if cpu.avx512 {
+                kernel!(f, "_avx512")
+            } else if cpu.avx2 {
+                kernel!(f, "_avx2")
+            } else if cpu.avx {
+                kernel!(f, "_avx")
+            } else if cpu.sse4_1 {
+                kernel!(f, "_sse")
+            } else {
+                kernel!(f, "_base")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispatch_rules() {
+        let none = CpuFeatures::default();
+        assert_eq!(dispatch(Function::EcEncode, &none), "ec_encode_data_base");
+        assert_eq!(none.to_string(), "none");
+
+        if cfg!(target_arch = "x86_64") {
+            let avx2_gfni = CpuFeatures {
+                sse4_1: true,
+                avx: true,
+                avx2: true,
+                gfni: true,
+                ..none
+            };
+            assert_eq!(
+                dispatch(Function::EcEncode, &avx2_gfni),
+                "ec_encode_data_avx2_gfni"
+            );
+            assert_eq!(dispatch(Function::GfVectMul, &avx2_gfni), "gf_vect_mul_avx");
+            assert_eq!(dispatch(Function::PqGen, &avx2_gfni), "pq_gen_avx2");
+            assert_eq!(dispatch(Function::XorGen, &avx2_gfni), "xor_gen_avx");
+            assert_eq!(avx2_gfni.to_string(), "sse4.1 avx avx2 gfni");
+        }
+    }
+
+    #[test]
+    fn test_dispatched_impl_names_entry_point() {
+        let cpu = cpu_features();
+        for f in [
+            Function::EcEncode,
+            Function::EcEncodeUpdate,
+            Function::GfVectDotProd,
+            Function::GfVectMad,
+            Function::GfVectMul,
+            Function::XorGen,
+            Function::PqGen,
+        ] {
+            let name = dispatched_impl(f);
+            assert!(name.starts_with(f.name()), "{name}");
+            if cfg!(target_arch = "x86_64") && cpu.avx2 {
+                assert!(!name.ends_with("_base"), "{name}");
+            }
+        }
+    }
+}
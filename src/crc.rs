@@ -0,0 +1,119 @@
+//! CRC16/32/64 checksums over byte slices.
+//!
+//! Every function takes the running CRC as `init`, so a checksum can be
+//! computed incrementally by feeding each chunk the previous result. The
+//! seeding and final inversion follow the ISA-L conventions documented in
+//! `crc.h` / `crc64.h`.
+
+use std::os::raw::{c_int, c_uchar};
+
+use crate::sys;
+
+/// CRC16 T10-DIF.
+pub fn crc16_t10dif(init: u16, data: &[u8]) -> u16 {
+    unsafe { sys::crc16_t10dif(init, data.as_ptr(), data.len() as u64) }
+}
+
+/// CRC16 T10-DIF of `src`, copying `src` into `dst` in the same pass.
+///
+/// # Panics
+/// Panics if `dst` and `src` differ in length.
+pub fn crc16_t10dif_copy(init: u16, dst: &mut [u8], src: &[u8]) -> u16 {
+    assert_eq!(dst.len(), src.len(), "dst and src must be the same length");
+    unsafe {
+        sys::crc16_t10dif_copy(
+            init,
+            dst.as_mut_ptr(),
+            src.as_ptr() as *mut u8,
+            src.len() as u64,
+        )
+    }
+}
+
+/// CRC32 IEEE (normal polynomial 0x04C11DB7).
+pub fn crc32_ieee(init: u32, data: &[u8]) -> u32 {
+    unsafe { sys::crc32_ieee(init, data.as_ptr(), data.len() as u64) }
+}
+
+/// CRC32 gzip/zlib (reflected polynomial, RFC 1952).
+pub fn crc32_gzip_refl(init: u32, data: &[u8]) -> u32 {
+    unsafe { sys::crc32_gzip_refl(init, data.as_ptr(), data.len() as u64) }
+}
+
+/// CRC32 iSCSI (Castagnoli). ISA-L does not invert the result; seed with
+/// `0xFFFF_FFFF` and invert the final value for standard CRC-32C.
+pub fn crc32_iscsi(init: u32, data: &[u8]) -> u32 {
+    // The C length parameter is an int, so feed oversized inputs in pieces.
+    data.chunks(c_int::MAX as usize)
+        .fold(init, |crc, chunk| unsafe {
+            sys::crc32_iscsi(chunk.as_ptr() as *mut c_uchar, chunk.len() as c_int, crc)
+        })
+}
+
+macro_rules! crc64_fns {
+    ($($(#[$doc:meta])* $name:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(init: u64, data: &[u8]) -> u64 {
+                unsafe { sys::$name(init, data.as_ptr(), data.len() as u64) }
+            }
+        )*
+    };
+}
+
+crc64_fns! {
+    /// CRC64 ECMA-182, reflected.
+    crc64_ecma_refl;
+    /// CRC64 ECMA-182, normal.
+    crc64_ecma_norm;
+    /// CRC64 ISO, reflected.
+    crc64_iso_refl;
+    /// CRC64 ISO, normal.
+    crc64_iso_norm;
+    /// CRC64 Jones, reflected.
+    crc64_jones_refl;
+    /// CRC64 Jones, normal.
+    crc64_jones_norm;
+    /// CRC64 Rocksoft, reflected.
+    crc64_rocksoft_refl;
+    /// CRC64 Rocksoft, normal.
+    crc64_rocksoft_norm;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn test_check_values() {
+        assert_eq!(crc16_t10dif(0, CHECK), 0xD0DB);
+        assert_eq!(crc32_gzip_refl(0, CHECK), 0xCBF4_3926);
+        assert_eq!(crc64_ecma_refl(0, CHECK), 0x995D_C9BB_DF19_39FA);
+    }
+
+    #[test]
+    fn test_incremental() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let (a, b) = data.split_at(3_333);
+        assert_eq!(
+            crc32_gzip_refl(crc32_gzip_refl(0, a), b),
+            crc32_gzip_refl(0, &data)
+        );
+        assert_eq!(crc32_iscsi(crc32_iscsi(!0, a), b), crc32_iscsi(!0, &data));
+        assert_eq!(
+            crc64_rocksoft_norm(crc64_rocksoft_norm(0, a), b),
+            crc64_rocksoft_norm(0, &data)
+        );
+    }
+
+    #[test]
+    fn test_copy() {
+        let src: Vec<u8> = (0..=255).collect();
+        let mut dst = vec![0u8; src.len()];
+        let crc = crc16_t10dif_copy(0, &mut dst, &src);
+        assert_eq!(dst, src);
+        assert_eq!(crc, crc16_t10dif(0, &src));
+    }
+}
@@ -0,0 +1,814 @@
+//! Safe Reed-Solomon erasure coding over `ec_encode_data`.
+//!
+//! [`ErasureCoder`] builds a systematic encode matrix (Cauchy by default,
+//! see [`MatrixKind`]), expands it once with `ec_init_tables`, and then
+//! encodes parity shards or rebuilds lost shards in place.
+//!
+//! By default the multi-binary `ec_encode_data` picks the kernel; an
+//! explicit [`Backend`] can be pinned with [`ErasureCoder::with_backend`].
+//! [`ErasureCoder::par_encode`] and [`ErasureCoder::par_reconstruct`] spread
+//! large shards over scoped threads by column range.
+//!
+//! `gf_gen_rs_matrix` is not MDS for every `k + p` (6+5 and 22+4 already
+//! have undecodable loss patterns), so [`ErasureCoder::with_matrix`]
+//! refuses such configurations; [`is_mds`] checks one up front.
+
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
+use std::os::raw::{c_int, c_uchar};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::cpu::Backend;
+use crate::error::EcError;
+use crate::gf::{invert_matrix, GfMatrix};
+use crate::sys::*;
+
+pub(crate) type EncodeFn =
+    unsafe extern "C" fn(c_int, c_int, c_int, *mut c_uchar, *mut *mut c_uchar, *mut *mut c_uchar);
+type InitTablesFn = unsafe extern "C" fn(c_int, c_int, *mut c_uchar, *mut c_uchar);
+
+/// Largest column range handed to a single `ec_encode_data` call.
+const MAX_CALL_LEN: usize = c_int::MAX as usize;
+
+/// Columns per `ec_encode_data` call in the parallel coders: small enough
+/// that a 14-shard stripe's working set stays in L2.
+pub const PAR_CHUNK_LEN: usize = 32 << 10;
+
+/// Generator for the parity rows of the encode matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatrixKind {
+    /// `gf_gen_cauchy1_matrix`: every `k x k` submatrix is invertible, so any
+    /// `k` shards rebuild the data.
+    Cauchy,
+    /// `gf_gen_rs_matrix`: Vandermonde-style rows, the classic ISA-L
+    /// Reed-Solomon layout. Not guaranteed to recover every erasure
+    /// pattern for larger `k + p`.
+    Vandermonde,
+}
+
+impl MatrixKind {
+    /// Fills `matrix` with the `(k + p) x k` encode matrix.
+    pub(crate) fn generate(self, matrix: &mut [u8], k: usize, p: usize) {
+        let (m, k) = ((k + p) as c_int, k as c_int);
+        unsafe {
+            match self {
+                MatrixKind::Cauchy => gf_gen_cauchy1_matrix(matrix.as_mut_ptr(), m, k),
+                MatrixKind::Vandermonde => gf_gen_rs_matrix(matrix.as_mut_ptr(), m, k),
+            }
+        }
+    }
+}
+
+/// `is_mds` results for Vandermonde matrices already checked, by `(k, p)`.
+static MDS_CACHE: Mutex<Option<HashMap<(usize, usize), bool>>> = Mutex::new(None);
+
+/// Whether the `kind` encode matrix for `k` data and `p` parity shards is
+/// MDS, i.e. any `k` of the `k + p` shards rebuild the data.
+///
+/// Cauchy matrices always are. For Vandermonde matrices with `p > 3` every
+/// square submatrix of the parity rows is inverted with `gf_invert_matrix`
+/// until one is singular; that is equivalent to testing every `k`-subset of
+/// rows, but the work still grows combinatorially with `k` and `p`.
+/// Results are cached per `(k, p)`. Returns `false` for parameters
+/// [`ErasureCoder`] would reject.
+pub fn is_mds(k: usize, p: usize, kind: MatrixKind) -> bool {
+    if k == 0 || p == 0 || k + p > 255 {
+        return false;
+    }
+    match kind {
+        MatrixKind::Cauchy => return true,
+        // Parity row r is [1, a_r, a_r^2, ...] with a_r = 2^r. Any two or
+        // three of the first three rows form a (generalized) Vandermonde
+        // system over distinct points, which is invertible in GF(2^8).
+        MatrixKind::Vandermonde if p <= 3 => return true,
+        MatrixKind::Vandermonde => {}
+    }
+
+    let cache = || MDS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cached = cache().as_ref().and_then(|c| c.get(&(k, p)).copied());
+    if let Some(mds) = cached {
+        return mds;
+    }
+    // Searched unlocked so other `(k, p)` lookups don't queue behind it;
+    // racing threads may both compute the same (identical) result.
+    let encode = GfMatrix::systematic(k, p, kind);
+    let parity: Vec<usize> = (k..k + p).collect();
+    let mds = square_minors_invertible(&encode.select_rows(&parity).unwrap());
+    cache().get_or_insert_with(HashMap::new).insert((k, p), mds);
+    mds
+}
+
+/// Whether every square submatrix of `m` is invertible. For a systematic
+/// `[I; P]` matrix this holds for `P` exactly when any `k` rows of the
+/// whole matrix are invertible.
+fn square_minors_invertible(m: &GfMatrix) -> bool {
+    let mut minor = Vec::new();
+    for n in 1..=m.rows().min(m.cols()) {
+        let all = for_each_subset(m.rows(), n, |rows| {
+            for_each_subset(m.cols(), n, |cols| {
+                minor.clear();
+                for &r in rows {
+                    minor.extend(cols.iter().map(|&c| m.row(r)[c]));
+                }
+                invert_matrix(&minor, n).is_some()
+            })
+        });
+        if !all {
+            return false;
+        }
+    }
+    true
+}
+
+/// Calls `f` with every `r`-element subset of `0..n` in lexicographic
+/// order, stopping early and returning `false` once `f` does.
+pub(crate) fn for_each_subset(n: usize, r: usize, mut f: impl FnMut(&[usize]) -> bool) -> bool {
+    let mut idx: Vec<usize> = (0..r).collect();
+    loop {
+        if !f(&idx) {
+            return false;
+        }
+        // Advance the rightmost index that still has room.
+        let mut i = r;
+        loop {
+            if i == 0 {
+                return true;
+            }
+            i -= 1;
+            if idx[i] < n - r + i {
+                break;
+            }
+        }
+        idx[i] += 1;
+        for j in i + 1..r {
+            idx[j] = idx[j - 1] + 1;
+        }
+    }
+}
+
+/// Reed-Solomon coder for `k` data shards and `p` parity shards.
+#[derive(Clone)]
+pub struct ErasureCoder {
+    k: usize,
+    p: usize,
+    kind: MatrixKind,
+    /// `(k + p) x k` encode matrix; the top `k` rows are the identity.
+    matrix: Vec<u8>,
+    /// Table expansion of the `p` parity rows, in the backend's format.
+    tables: Vec<u8>,
+    /// Pinned kernel family; `None` defers to the dispatcher.
+    backend: Option<Backend>,
+}
+
+impl ErasureCoder {
+    /// Creates a coder with a Cauchy encode matrix, which is MDS for every
+    /// `k + p <= 255`.
+    pub fn new(k: usize, p: usize) -> Result<Self, EcError> {
+        Self::build(k, p, MatrixKind::Cauchy, None)
+    }
+
+    /// Like [`new`](Self::new), but with an explicit encode matrix.
+    ///
+    /// # Errors
+    /// [`EcError::NotMds`] if some loss of `p` or fewer shards could not be
+    /// rebuilt with this matrix (see [`is_mds`]).
+    pub fn with_matrix(k: usize, p: usize, kind: MatrixKind) -> Result<Self, EcError> {
+        Self::build(k, p, kind, None)
+    }
+
+    /// Like [`new`](Self::new), but always runs `backend`'s kernels.
+    ///
+    /// # Errors
+    /// [`EcError::UnsupportedBackend`] if the running CPU lacks the
+    /// instructions `backend` needs.
+    pub fn with_backend(k: usize, p: usize, backend: Backend) -> Result<Self, EcError> {
+        if !backend.is_supported() {
+            return Err(EcError::UnsupportedBackend(backend));
+        }
+        Self::build(k, p, MatrixKind::Cauchy, Some(backend))
+    }
+
+    fn build(
+        k: usize,
+        p: usize,
+        kind: MatrixKind,
+        backend: Option<Backend>,
+    ) -> Result<Self, EcError> {
+        if k == 0 || p == 0 || k + p > 255 {
+            return Err(EcError::InvalidParameters { k, p });
+        }
+        if !is_mds(k, p, kind) {
+            return Err(EcError::NotMds { k, p });
+        }
+
+        let mut matrix = vec![0u8; (k + p) * k];
+        kind.generate(&mut matrix, k, p);
+        let tables = init_tables(backend, k, p, &matrix[k * k..]);
+
+        Ok(ErasureCoder {
+            k,
+            p,
+            kind,
+            matrix,
+            tables,
+            backend,
+        })
+    }
+
+    /// Generator of the encode matrix.
+    pub fn matrix_kind(&self) -> MatrixKind {
+        self.kind
+    }
+
+    /// Number of data shards.
+    pub fn data_shards(&self) -> usize {
+        self.k
+    }
+
+    /// Number of parity shards.
+    pub fn parity_shards(&self) -> usize {
+        self.p
+    }
+
+    /// The `(k + p) x k` encode matrix, row-major.
+    pub fn matrix(&self) -> &[u8] {
+        &self.matrix
+    }
+
+    /// The pinned backend, or `None` if the dispatcher chooses.
+    pub fn backend(&self) -> Option<Backend> {
+        self.backend
+    }
+
+    /// Computes the `p` parity shards from the `k` data shards.
+    pub fn encode(&self, data: &[&[u8]], parity: &mut [&mut [u8]]) -> Result<(), EcError> {
+        let len = self.check_shards(data, parity)?;
+        let mut src: Vec<*mut c_uchar> = data.iter().map(|d| d.as_ptr() as *mut _).collect();
+        let mut dst: Vec<*mut c_uchar> = parity.iter_mut().map(|d| d.as_mut_ptr()).collect();
+        unsafe {
+            encode_raw(
+                encode_fn(self.backend),
+                len,
+                self.k,
+                self.p,
+                &self.tables,
+                &mut src,
+                &mut dst,
+            )
+        };
+        Ok(())
+    }
+
+    /// Rebuilds the shards listed in `erasures` in place.
+    ///
+    /// `shards` holds all `k + p` shards in order (data first, then parity),
+    /// each of the same length; the contents of erased shards are ignored and
+    /// overwritten.
+    pub fn reconstruct(&self, shards: &mut [&mut [u8]], erasures: &[usize]) -> Result<(), EcError> {
+        if let Some((survivors, tables)) = self.decode_plan(shards, erasures)? {
+            self.apply_decode(shards, &survivors, erasures, &tables);
+        }
+        Ok(())
+    }
+
+    /// Validates a reconstruct request and returns the survivors to read and
+    /// the decode tables, or `None` if nothing is erased.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn decode_plan(
+        &self,
+        shards: &[&mut [u8]],
+        erasures: &[usize],
+    ) -> Result<Option<(Vec<usize>, Vec<u8>)>, EcError> {
+        let n = self.k + self.p;
+        check_count(n, shards.len())?;
+        let len = shards[0].len();
+        if shards.iter().any(|s| s.len() != len) {
+            return Err(EcError::ShardLength);
+        }
+
+        let mut erased = vec![false; n];
+        for &e in erasures {
+            if e >= n || erased[e] {
+                return Err(EcError::InvalidErasure(e));
+            }
+            erased[e] = true;
+        }
+        if erasures.len() > self.p {
+            return Err(EcError::TooManyErasures {
+                erasures: erasures.len(),
+                parity: self.p,
+            });
+        }
+        if erasures.is_empty() {
+            return Ok(None);
+        }
+
+        let survivors: Vec<usize> = (0..n).filter(|&i| !erased[i]).take(self.k).collect();
+        let decode = self.decode_matrix(&survivors, erasures)?;
+        let tables = init_tables(self.backend, self.k, erasures.len(), &decode);
+        Ok(Some((survivors, tables)))
+    }
+
+    /// Runs a decode from [`decode_plan`](Self::decode_plan) over `shards`,
+    /// which have already been validated.
+    fn apply_decode(
+        &self,
+        shards: &mut [&mut [u8]],
+        survivors: &[usize],
+        erasures: &[usize],
+        tables: &[u8],
+    ) {
+        let len = shards[0].len();
+        let ptrs: Vec<*mut c_uchar> = shards.iter_mut().map(|s| s.as_mut_ptr()).collect();
+        let mut src: Vec<*mut c_uchar> = survivors.iter().map(|&i| ptrs[i]).collect();
+        let mut dst: Vec<*mut c_uchar> = erasures.iter().map(|&i| ptrs[i]).collect();
+        unsafe {
+            encode_raw(
+                encode_fn(self.backend),
+                len,
+                self.k,
+                erasures.len(),
+                tables,
+                &mut src,
+                &mut dst,
+            )
+        };
+    }
+
+    /// Like [`encode`](Self::encode), but splits the shards into column
+    /// ranges and encodes them on up to `threads` scoped threads; 0 uses
+    /// [`thread::available_parallelism`]. Each thread works through its
+    /// range [`PAR_CHUNK_LEN`] columns per `ec_encode_data` call, so there
+    /// is no limit on the shard length.
+    pub fn par_encode(
+        &self,
+        data: &[&[u8]],
+        parity: &mut [&mut [u8]],
+        threads: usize,
+    ) -> Result<(), EcError> {
+        let len = self.check_shards(data, parity)?;
+        let spans = column_spans(len, threads);
+        let pieces = split_columns(parity, &spans);
+        thread::scope(|s| {
+            for (span, mut parity) in spans.into_iter().zip(pieces) {
+                s.spawn(move || self.encode_span(data, span, &mut parity));
+            }
+        });
+        Ok(())
+    }
+
+    /// Like [`reconstruct`](Self::reconstruct), but rebuilds column ranges
+    /// on up to `threads` scoped threads as [`par_encode`](Self::par_encode)
+    /// does. The decode matrix is inverted once and shared.
+    pub fn par_reconstruct(
+        &self,
+        shards: &mut [&mut [u8]],
+        erasures: &[usize],
+        threads: usize,
+    ) -> Result<(), EcError> {
+        let (survivors, tables) = match self.decode_plan(shards, erasures)? {
+            Some(plan) => plan,
+            None => return Ok(()),
+        };
+
+        let spans = column_spans(shards[0].len(), threads);
+        let pieces = split_columns(shards, &spans);
+        thread::scope(|s| {
+            for mut shards in pieces {
+                let (survivors, tables) = (&survivors, &tables);
+                s.spawn(move || self.decode_span(&mut shards, survivors, erasures, tables));
+            }
+        });
+        Ok(())
+    }
+
+    /// Checks the shard counts and lengths for an encode, returning the
+    /// shard length.
+    pub(crate) fn check_shards(
+        &self,
+        data: &[&[u8]],
+        parity: &[&mut [u8]],
+    ) -> Result<usize, EcError> {
+        check_stripe(self.k, self.p, data, parity)
+    }
+
+    /// Encodes the `span` columns of `data` into `parity`, which holds just
+    /// those columns, [`PAR_CHUNK_LEN`] at a time.
+    pub(crate) fn encode_span(&self, data: &[&[u8]], span: Range<usize>, parity: &mut [&mut [u8]]) {
+        for off in (0..span.len()).step_by(PAR_CHUNK_LEN) {
+            let end = span.len().min(off + PAR_CHUNK_LEN);
+            let mut src: Vec<*mut c_uchar> = data
+                .iter()
+                .map(|d| d[span.start + off..].as_ptr() as *mut _)
+                .collect();
+            let mut dst: Vec<*mut c_uchar> =
+                parity.iter_mut().map(|d| d[off..].as_mut_ptr()).collect();
+            unsafe {
+                encode_raw(
+                    encode_fn(self.backend),
+                    end - off,
+                    self.k,
+                    self.p,
+                    &self.tables,
+                    &mut src,
+                    &mut dst,
+                )
+            };
+        }
+    }
+
+    /// Runs a decode plan over one column range of the shards,
+    /// [`PAR_CHUNK_LEN`] columns at a time.
+    pub(crate) fn decode_span(
+        &self,
+        shards: &mut [&mut [u8]],
+        survivors: &[usize],
+        erasures: &[usize],
+        tables: &[u8],
+    ) {
+        let len = shards[0].len();
+        for off in (0..len).step_by(PAR_CHUNK_LEN) {
+            let end = len.min(off + PAR_CHUNK_LEN);
+            let mut shards: Vec<&mut [u8]> = shards.iter_mut().map(|d| &mut d[off..end]).collect();
+            self.apply_decode(&mut shards, survivors, erasures, tables);
+        }
+    }
+
+    /// Builds the `erasures.len() x k` matrix that maps the `survivors` rows
+    /// back to the erased rows.
+    fn decode_matrix(&self, survivors: &[usize], erasures: &[usize]) -> Result<Vec<u8>, EcError> {
+        let k = self.k;
+        let mut b = Vec::with_capacity(k * k);
+        for &r in survivors {
+            b.extend_from_slice(&self.matrix[r * k..(r + 1) * k]);
+        }
+        let mut inv = vec![0u8; k * k];
+        if unsafe { gf_invert_matrix(b.as_mut_ptr(), inv.as_mut_ptr(), k as c_int) } != 0 {
+            return Err(EcError::SingularMatrix);
+        }
+
+        let mut decode = vec![0u8; erasures.len() * k];
+        for (row, &e) in decode.chunks_mut(k).zip(erasures) {
+            if e < k {
+                row.copy_from_slice(&inv[e * k..(e + 1) * k]);
+            } else {
+                let enc = &self.matrix[e * k..(e + 1) * k];
+                for (i, out) in row.iter_mut().enumerate() {
+                    *out = (0..k).fold(0u8, |acc, j| {
+                        acc ^ unsafe { gf_mul(enc[j], inv[j * k + i]) }
+                    });
+                }
+            }
+        }
+        Ok(decode)
+    }
+}
+
+pub(crate) fn check_count(expected: usize, actual: usize) -> Result<(), EcError> {
+    if expected != actual {
+        return Err(EcError::ShardCount { expected, actual });
+    }
+    Ok(())
+}
+
+/// Checks for `k` data and `p` parity shards of one length, returning it.
+pub(crate) fn check_stripe(
+    k: usize,
+    p: usize,
+    data: &[&[u8]],
+    parity: &[&mut [u8]],
+) -> Result<usize, EcError> {
+    check_count(k, data.len())?;
+    check_count(p, parity.len())?;
+    let len = data[0].len();
+    if data.iter().any(|d| d.len() != len) || parity.iter().any(|d| d.len() != len) {
+        return Err(EcError::ShardLength);
+    }
+    Ok(len)
+}
+
+/// Splits `len` columns into one span per worker, each a whole number of
+/// [`PAR_CHUNK_LEN`] chunks except the last.
+pub(crate) fn column_spans(len: usize, threads: usize) -> Vec<Range<usize>> {
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let chunks = len.div_ceil(PAR_CHUNK_LEN);
+    let per = chunks.div_ceil(threads.min(chunks).max(1)).max(1) * PAR_CHUNK_LEN;
+    (0..len)
+        .step_by(per)
+        .map(|start| start..len.min(start + per))
+        .collect()
+}
+
+/// Cuts every shard at the span boundaries, returning the pieces grouped
+/// by span.
+pub(crate) fn split_columns<'a>(
+    shards: &'a mut [&mut [u8]],
+    spans: &[Range<usize>],
+) -> Vec<Vec<&'a mut [u8]>> {
+    let mut pieces: Vec<Vec<&mut [u8]>> = spans
+        .iter()
+        .map(|_| Vec::with_capacity(shards.len()))
+        .collect();
+    for shard in shards.iter_mut() {
+        let mut rest: &mut [u8] = shard;
+        for (span, piece) in spans.iter().zip(&mut pieces) {
+            let (head, tail) = mem::take(&mut rest).split_at_mut(span.len());
+            piece.push(head);
+            rest = tail;
+        }
+    }
+    pieces
+}
+
+/// Expands the `rows x k` coefficient matrix `a` into the table format
+/// `backend` consumes.
+fn init_tables(backend: Option<Backend>, k: usize, rows: usize, a: &[u8]) -> Vec<u8> {
+    let (init, per_coef): (InitTablesFn, usize) = match backend {
+        // The dispatched ec_init_tables may emit either format; 32 bytes per
+        // coefficient covers both.
+        None => (ec_init_tables, 32),
+        Some(b) if b.uses_gfni_tables() => (ec_init_tables_gfni, 8),
+        Some(_) => (ec_init_tables_base, 32),
+    };
+    let mut tables = vec![0u8; k * rows * per_coef];
+    unsafe {
+        init(
+            k as c_int,
+            rows as c_int,
+            a.as_ptr() as *mut _,
+            tables.as_mut_ptr(),
+        )
+    };
+    tables
+}
+
+/// The encode kernel for `backend`. Backends other than `Base` are only
+/// constructed after [`Backend::is_supported`], which is false off x86_64.
+pub(crate) fn encode_fn(backend: Option<Backend>) -> EncodeFn {
+    match backend {
+        None => ec_encode_data,
+        Some(Backend::Base) => ec_encode_data_base,
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Some(b) => match b {
+            Backend::Base => ec_encode_data_base,
+            Backend::Sse => ec_encode_data_sse,
+            Backend::Avx => ec_encode_data_avx,
+            Backend::Avx2 => ec_encode_data_avx2,
+            Backend::Avx512 => ec_encode_data_avx512,
+            Backend::Avx2Gfni => ec_encode_data_avx2_gfni,
+            Backend::Avx512Gfni => ec_encode_data_avx512_gfni,
+        },
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        Some(b) => unreachable!("{b} backend is not available on this target"),
+    }
+}
+
+/// Runs `encode` over `len` columns, splitting calls so each length fits
+/// the C `int` parameter.
+///
+/// # Safety
+/// Every pointer in `src` and `dst` must be valid for `len` bytes, and
+/// `tables` must be in the format `encode` expects.
+pub(crate) unsafe fn encode_raw(
+    encode: EncodeFn,
+    len: usize,
+    k: usize,
+    rows: usize,
+    tables: &[u8],
+    src: &mut [*mut c_uchar],
+    dst: &mut [*mut c_uchar],
+) {
+    let mut off = 0;
+    while off < len {
+        let n = (len - off).min(MAX_CALL_LEN);
+        let mut s: Vec<*mut c_uchar> = src.iter().map(|p| p.add(off)).collect();
+        let mut d: Vec<*mut c_uchar> = dst.iter().map(|p| p.add(off)).collect();
+        encode(
+            n as c_int,
+            k as c_int,
+            rows as c_int,
+            tables.as_ptr() as *mut _,
+            s.as_mut_ptr(),
+            d.as_mut_ptr(),
+        );
+        off += n;
+    }
+}
+
+/// A stripe of `total` shards of `len` bytes, the first `k` filled with a
+/// pattern and the rest written by `encode`. Shared by the coder tests.
+#[cfg(test)]
+pub(crate) fn test_stripe(
+    k: usize,
+    total: usize,
+    len: usize,
+    encode: impl FnOnce(&[&[u8]], &mut [&mut [u8]]) -> Result<(), EcError>,
+) -> Vec<Vec<u8>> {
+    let mut shards: Vec<Vec<u8>> = (0..total)
+        .map(|s| (0..len).map(|i| (i * 29 + s * 101 + 7) as u8).collect())
+        .collect();
+    let (data, parity) = shards.split_at_mut(k);
+    let data: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+    let mut parity: Vec<&mut [u8]> = parity.iter_mut().map(|d| &mut d[..]).collect();
+    encode(&data, &mut parity).unwrap();
+    shards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shards(k: usize, p: usize, len: usize) -> Vec<Vec<u8>> {
+        (0..k + p)
+            .map(|i| {
+                if i < k {
+                    (0..len).map(|j| (i * 31 + j * 7) as u8).collect()
+                } else {
+                    vec![0u8; len]
+                }
+            })
+            .collect()
+    }
+
+    fn encode(coder: &ErasureCoder, all: &mut [Vec<u8>]) {
+        let (data, parity) = all.split_at_mut(coder.data_shards());
+        let data: Vec<&[u8]> = data.iter().map(|d| d.as_slice()).collect();
+        let mut parity: Vec<&mut [u8]> = parity.iter_mut().map(|d| d.as_mut_slice()).collect();
+        coder.encode(&data, &mut parity).unwrap();
+    }
+
+    #[test]
+    fn test_encode_reconstruct() {
+        let (k, p, len) = (6, 3, 1000);
+        let coder = ErasureCoder::new(k, p).unwrap();
+        let mut all = shards(k, p, len);
+        encode(&coder, &mut all);
+        let original = all.clone();
+
+        for erasures in [vec![0], vec![1, 7], vec![2, 5, 8], vec![6, 7, 8]] {
+            let mut damaged = original.clone();
+            for &e in &erasures {
+                damaged[e].iter_mut().for_each(|b| *b = 0xAA);
+            }
+            let mut refs: Vec<&mut [u8]> = damaged.iter_mut().map(|s| s.as_mut_slice()).collect();
+            coder.reconstruct(&mut refs, &erasures).unwrap();
+            assert_eq!(damaged, original, "erasures {erasures:?}");
+        }
+    }
+
+    #[test]
+    fn test_vandermonde_matrix() {
+        let (k, p, len) = (4, 2, 256);
+        let coder = ErasureCoder::with_matrix(k, p, MatrixKind::Vandermonde).unwrap();
+        assert_eq!(coder.matrix_kind(), MatrixKind::Vandermonde);
+        assert_ne!(coder.matrix(), ErasureCoder::new(k, p).unwrap().matrix());
+
+        let mut all = shards(k, p, len);
+        encode(&coder, &mut all);
+        let original = all.clone();
+        all[0].iter_mut().for_each(|b| *b = 0);
+        all[3].iter_mut().for_each(|b| *b = 0);
+        let mut refs: Vec<&mut [u8]> = all.iter_mut().map(|s| s.as_mut_slice()).collect();
+        coder.reconstruct(&mut refs, &[0, 3]).unwrap();
+        assert_eq!(all, original);
+    }
+
+    #[test]
+    fn test_is_mds() {
+        assert!(is_mds(10, 4, MatrixKind::Cauchy));
+        assert!(is_mds(200, 3, MatrixKind::Vandermonde));
+        assert!(is_mds(5, 5, MatrixKind::Vandermonde));
+        assert!(is_mds(21, 4, MatrixKind::Vandermonde));
+        assert!(!is_mds(6, 5, MatrixKind::Vandermonde));
+        assert!(!is_mds(22, 4, MatrixKind::Vandermonde));
+        assert!(!is_mds(0, 4, MatrixKind::Cauchy));
+
+        // The shortcuts agree with the exhaustive check.
+        for (k, p, kind) in [(8, 4, MatrixKind::Cauchy), (30, 3, MatrixKind::Vandermonde)] {
+            let parity: Vec<usize> = (k..k + p).collect();
+            let m = GfMatrix::systematic(k, p, kind)
+                .select_rows(&parity)
+                .unwrap();
+            assert!(square_minors_invertible(&m), "{k}+{p} {kind:?}");
+        }
+
+        assert_eq!(
+            ErasureCoder::with_matrix(6, 5, MatrixKind::Vandermonde).err(),
+            Some(EcError::NotMds { k: 6, p: 5 })
+        );
+    }
+
+    #[test]
+    fn test_subsets() {
+        let mut seen = Vec::new();
+        assert!(for_each_subset(4, 2, |s| {
+            seen.push(s.to_vec());
+            true
+        }));
+        assert_eq!(
+            seen,
+            [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]].map(|s| s.to_vec())
+        );
+        assert!(!for_each_subset(5, 3, |s| s != [0, 2, 4]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            ErasureCoder::new(0, 2).err(),
+            Some(EcError::InvalidParameters { k: 0, p: 2 })
+        );
+
+        let coder = ErasureCoder::new(4, 2).unwrap();
+        let mut all = shards(4, 2, 64);
+        let mut refs: Vec<&mut [u8]> = all.iter_mut().map(|s| s.as_mut_slice()).collect();
+        assert_eq!(
+            coder.reconstruct(&mut refs, &[0, 1, 2]),
+            Err(EcError::TooManyErasures {
+                erasures: 3,
+                parity: 2
+            })
+        );
+        assert_eq!(
+            coder.reconstruct(&mut refs, &[1, 1]),
+            Err(EcError::InvalidErasure(1))
+        );
+        assert_eq!(
+            coder.reconstruct(&mut refs[..5], &[0]),
+            Err(EcError::ShardCount {
+                expected: 6,
+                actual: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_backends_match_dispatcher() {
+        let (k, p, len) = (5, 4, 1000 + 13);
+        let coder = ErasureCoder::new(k, p).unwrap();
+        let mut expected = shards(k, p, len);
+        encode(&coder, &mut expected);
+
+        for backend in Backend::ALL {
+            if !backend.is_supported() {
+                assert_eq!(
+                    ErasureCoder::with_backend(k, p, backend).err(),
+                    Some(EcError::UnsupportedBackend(backend))
+                );
+                continue;
+            }
+            let pinned = ErasureCoder::with_backend(k, p, backend).unwrap();
+            assert_eq!(pinned.backend(), Some(backend));
+            let mut all = shards(k, p, len);
+            encode(&pinned, &mut all);
+            assert_eq!(all, expected, "{backend}");
+
+            all[1].iter_mut().for_each(|b| *b = 0);
+            all[k + 2].iter_mut().for_each(|b| *b = 0);
+            let mut refs: Vec<&mut [u8]> = all.iter_mut().map(|s| s.as_mut_slice()).collect();
+            pinned.reconstruct(&mut refs, &[1, k + 2]).unwrap();
+            assert_eq!(all, expected, "{backend} reconstruct");
+        }
+        assert!(Backend::detect().is_supported());
+    }
+
+    #[test]
+    fn test_par_encode_reconstruct() {
+        let (k, p) = (4, 3);
+        let coder = ErasureCoder::new(k, p).unwrap();
+        for len in [0, 100, 3 * PAR_CHUNK_LEN + 77] {
+            let mut expected = shards(k, p, len);
+            encode(&coder, &mut expected);
+
+            for threads in [0, 1, 3, 64] {
+                let mut all = shards(k, p, len);
+                let (data, parity) = all.split_at_mut(k);
+                let data: Vec<&[u8]> = data.iter().map(|d| d.as_slice()).collect();
+                let mut parity: Vec<&mut [u8]> =
+                    parity.iter_mut().map(|d| d.as_mut_slice()).collect();
+                coder.par_encode(&data, &mut parity, threads).unwrap();
+                assert_eq!(all, expected, "len {len} threads {threads}");
+
+                all[0].iter_mut().for_each(|b| *b = 0);
+                all[5].iter_mut().for_each(|b| *b = 0);
+                let mut refs: Vec<&mut [u8]> = all.iter_mut().map(|s| s.as_mut_slice()).collect();
+                coder.par_reconstruct(&mut refs, &[0, 5], threads).unwrap();
+                assert_eq!(all, expected, "len {len} threads {threads} reconstruct");
+            }
+        }
+    }
+
+    #[test]
+    fn test_column_spans() {
+        assert!(column_spans(0, 4).is_empty());
+        assert_eq!(column_spans(10, 4), vec![0..10]);
+        let len = 5 * PAR_CHUNK_LEN + 1;
+        let spans = column_spans(len, 2);
+        assert_eq!(spans, vec![0..3 * PAR_CHUNK_LEN, 3 * PAR_CHUNK_LEN..len]);
+    }
+}
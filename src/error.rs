@@ -0,0 +1,543 @@
+//! Typed errors for the safe wrappers.
+//!
+//! The C API reports failures as `c_int` codes whose numeric spaces overlap
+//! (`-3` is both `ISAL_INVALID_STATE` for deflate and `ISAL_INVALID_LOOKBACK`
+//! for inflate), so each subsystem gets its own enum. `from_code` maps a C
+//! return value to the matching variant and `code` maps it back.
+
+use std::error::Error;
+use std::fmt;
+use std::os::raw::c_int;
+
+use crate::cpu::Backend;
+use crate::sys::*;
+
+/// Errors returned by `isal_deflate` / `isal_deflate_stateless` and the
+/// compressor setup checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeflateError {
+    /// `STATELESS_OVERFLOW`: output buffer too small for stateless deflate.
+    StatelessOverflow,
+    /// `ISAL_INVALID_STATE`: stream is not in a state that accepts this call.
+    InvalidState,
+    /// `ISAL_INVALID_LEVEL`: compression level out of range.
+    InvalidLevel,
+    /// `ISAL_INVALID_LEVEL_BUF`: level buffer missing or too small.
+    InvalidLevelBuf,
+    /// `INVALID_FLUSH`: unsupported flush flag.
+    InvalidFlush,
+    /// `INVALID_PARAM`: invalid stream parameter (e.g. history bits).
+    InvalidParam,
+    /// `ISAL_INVALID_OPERATION`: operation not allowed on this stream.
+    InvalidOperation,
+    /// Any other non-zero return code.
+    Unknown(c_int),
+}
+
+impl DeflateError {
+    /// Maps a deflate return code to an error; `COMP_OK` yields `None`.
+    pub fn from_code(code: c_int) -> Option<Self> {
+        match code {
+            COMP_OK => None,
+            STATELESS_OVERFLOW => Some(DeflateError::StatelessOverflow),
+            ISAL_INVALID_STATE => Some(DeflateError::InvalidState),
+            ISAL_INVALID_LEVEL => Some(DeflateError::InvalidLevel),
+            ISAL_INVALID_LEVEL_BUF => Some(DeflateError::InvalidLevelBuf),
+            INVALID_FLUSH => Some(DeflateError::InvalidFlush),
+            INVALID_PARAM => Some(DeflateError::InvalidParam),
+            ISAL_INVALID_OPERATION => Some(DeflateError::InvalidOperation),
+            other => Some(DeflateError::Unknown(other)),
+        }
+    }
+
+    /// The C return code for this error.
+    pub fn code(&self) -> c_int {
+        match *self {
+            DeflateError::StatelessOverflow => STATELESS_OVERFLOW,
+            DeflateError::InvalidState => ISAL_INVALID_STATE,
+            DeflateError::InvalidLevel => ISAL_INVALID_LEVEL,
+            DeflateError::InvalidLevelBuf => ISAL_INVALID_LEVEL_BUF,
+            DeflateError::InvalidFlush => INVALID_FLUSH,
+            DeflateError::InvalidParam => INVALID_PARAM,
+            DeflateError::InvalidOperation => ISAL_INVALID_OPERATION,
+            DeflateError::Unknown(code) => code,
+        }
+    }
+
+    pub(crate) fn check(code: c_int) -> Result<(), Self> {
+        match Self::from_code(code) {
+            None => Ok(()),
+            Some(e) => Err(e),
+        }
+    }
+}
+
+impl fmt::Display for DeflateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DeflateError::StatelessOverflow => f.write_str("output buffer too small"),
+            DeflateError::InvalidState => f.write_str("invalid compression stream state"),
+            DeflateError::InvalidLevel => f.write_str("invalid compression level"),
+            DeflateError::InvalidLevelBuf => f.write_str("missing or undersized level buffer"),
+            DeflateError::InvalidFlush => f.write_str("invalid flush flag"),
+            DeflateError::InvalidParam => f.write_str("invalid compression parameter"),
+            DeflateError::InvalidOperation => f.write_str("invalid compression operation"),
+            DeflateError::Unknown(code) => write!(f, "unknown deflate error {code}"),
+        }
+    }
+}
+
+impl Error for DeflateError {}
+
+/// Errors returned by `isal_inflate` / `isal_inflate_stateless` and the
+/// header readers.
+///
+/// ISA-L reports the informational results `ISAL_END_INPUT`,
+/// `ISAL_OUT_OVERFLOW`, `ISAL_*_OVERFLOW` and `ISAL_NEED_DICT` as positive
+/// codes; the safe wrappers surface them as errors when they prevent a
+/// complete decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InflateError {
+    /// `ISAL_END_INPUT`: input ended before the final block.
+    EndOfInput,
+    /// `ISAL_OUT_OVERFLOW`: output buffer too small.
+    OutputOverflow,
+    /// `ISAL_NAME_OVERFLOW`: gzip name field larger than the header buffer.
+    NameOverflow,
+    /// `ISAL_COMMENT_OVERFLOW`: gzip comment larger than the header buffer.
+    CommentOverflow,
+    /// `ISAL_EXTRA_OVERFLOW`: gzip extra field larger than the header buffer.
+    ExtraOverflow,
+    /// `ISAL_NEED_DICT`: the zlib stream requires a preset dictionary.
+    NeedDict,
+    /// `ISAL_INVALID_BLOCK`: malformed deflate block header.
+    InvalidBlock,
+    /// `ISAL_INVALID_SYMBOL`: invalid Huffman symbol.
+    InvalidSymbol,
+    /// `ISAL_INVALID_LOOKBACK`: back-reference beyond the history window.
+    InvalidLookback,
+    /// `ISAL_INVALID_WRAPPER`: malformed gzip/zlib wrapper.
+    InvalidWrapper,
+    /// `ISAL_UNSUPPORTED_METHOD`: wrapper declares a method other than deflate.
+    UnsupportedMethod,
+    /// `ISAL_INCORRECT_CHECKSUM`: trailer checksum mismatch.
+    IncorrectChecksum,
+    /// Any other non-zero return code.
+    Unknown(c_int),
+}
+
+impl InflateError {
+    /// Maps an inflate return code to an error; `ISAL_DECOMP_OK` yields `None`.
+    pub fn from_code(code: c_int) -> Option<Self> {
+        match code {
+            ISAL_DECOMP_OK => None,
+            ISAL_END_INPUT => Some(InflateError::EndOfInput),
+            ISAL_OUT_OVERFLOW => Some(InflateError::OutputOverflow),
+            ISAL_NAME_OVERFLOW => Some(InflateError::NameOverflow),
+            ISAL_COMMENT_OVERFLOW => Some(InflateError::CommentOverflow),
+            ISAL_EXTRA_OVERFLOW => Some(InflateError::ExtraOverflow),
+            ISAL_NEED_DICT => Some(InflateError::NeedDict),
+            ISAL_INVALID_BLOCK => Some(InflateError::InvalidBlock),
+            ISAL_INVALID_SYMBOL => Some(InflateError::InvalidSymbol),
+            ISAL_INVALID_LOOKBACK => Some(InflateError::InvalidLookback),
+            ISAL_INVALID_WRAPPER => Some(InflateError::InvalidWrapper),
+            ISAL_UNSUPPORTED_METHOD => Some(InflateError::UnsupportedMethod),
+            ISAL_INCORRECT_CHECKSUM => Some(InflateError::IncorrectChecksum),
+            other => Some(InflateError::Unknown(other)),
+        }
+    }
+
+    /// The C return code for this error.
+    pub fn code(&self) -> c_int {
+        match *self {
+            InflateError::EndOfInput => ISAL_END_INPUT,
+            InflateError::OutputOverflow => ISAL_OUT_OVERFLOW,
+            InflateError::NameOverflow => ISAL_NAME_OVERFLOW,
+            InflateError::CommentOverflow => ISAL_COMMENT_OVERFLOW,
+            InflateError::ExtraOverflow => ISAL_EXTRA_OVERFLOW,
+            InflateError::NeedDict => ISAL_NEED_DICT,
+            InflateError::InvalidBlock => ISAL_INVALID_BLOCK,
+            InflateError::InvalidSymbol => ISAL_INVALID_SYMBOL,
+            InflateError::InvalidLookback => ISAL_INVALID_LOOKBACK,
+            InflateError::InvalidWrapper => ISAL_INVALID_WRAPPER,
+            InflateError::UnsupportedMethod => ISAL_UNSUPPORTED_METHOD,
+            InflateError::IncorrectChecksum => ISAL_INCORRECT_CHECKSUM,
+            InflateError::Unknown(code) => code,
+        }
+    }
+
+    pub(crate) fn check(code: c_int) -> Result<(), Self> {
+        match Self::from_code(code) {
+            None => Ok(()),
+            Some(e) => Err(e),
+        }
+    }
+}
+
+impl fmt::Display for InflateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            InflateError::EndOfInput => f.write_str("unexpected end of compressed input"),
+            InflateError::OutputOverflow => f.write_str("output buffer too small"),
+            InflateError::NameOverflow => f.write_str("gzip name field overflow"),
+            InflateError::CommentOverflow => f.write_str("gzip comment field overflow"),
+            InflateError::ExtraOverflow => f.write_str("gzip extra field overflow"),
+            InflateError::NeedDict => f.write_str("stream requires a preset dictionary"),
+            InflateError::InvalidBlock => f.write_str("invalid deflate block"),
+            InflateError::InvalidSymbol => f.write_str("invalid huffman symbol"),
+            InflateError::InvalidLookback => f.write_str("invalid lookback distance"),
+            InflateError::InvalidWrapper => f.write_str("invalid gzip/zlib wrapper"),
+            InflateError::UnsupportedMethod => f.write_str("unsupported compression method"),
+            InflateError::IncorrectChecksum => f.write_str("checksum mismatch"),
+            InflateError::Unknown(code) => write!(f, "unknown inflate error {code}"),
+        }
+    }
+}
+
+impl Error for InflateError {}
+
+/// Errors from the safe erasure-coding wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcError {
+    /// `k` or `p` is zero, or `k + p` exceeds the 255 rows GF(2^8) supports.
+    InvalidParameters { k: usize, p: usize },
+    /// Wrong number of shard buffers.
+    ShardCount { expected: usize, actual: usize },
+    /// Shard buffers do not all have the same length.
+    ShardLength,
+    /// An erasure index is out of range or listed twice.
+    InvalidErasure(usize),
+    /// More shards were lost than there are parity shards.
+    TooManyErasures { erasures: usize, parity: usize },
+    /// `gf_invert_matrix` found the decode matrix singular.
+    SingularMatrix,
+    /// The encode matrix cannot rebuild every loss of up to `p` shards.
+    NotMds { k: usize, p: usize },
+    /// The running CPU cannot execute the requested kernel family.
+    UnsupportedBackend(Backend),
+    /// The surviving shards do not determine the lost ones.
+    Unrecoverable,
+    /// The shard length must be a multiple of `required`.
+    UnalignedShardLength { required: usize },
+}
+
+impl fmt::Display for EcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            EcError::InvalidParameters { k, p } => {
+                write!(f, "invalid erasure code parameters k={k} p={p}")
+            }
+            EcError::ShardCount { expected, actual } => {
+                write!(f, "expected {expected} shards, got {actual}")
+            }
+            EcError::ShardLength => f.write_str("shards differ in length"),
+            EcError::InvalidErasure(i) => write!(f, "invalid erasure index {i}"),
+            EcError::TooManyErasures { erasures, parity } => {
+                write!(f, "{erasures} erasures exceed {parity} parity shards")
+            }
+            EcError::SingularMatrix => f.write_str("decode matrix is singular"),
+            EcError::NotMds { k, p } => {
+                write!(f, "encode matrix for k={k} p={p} is not MDS")
+            }
+            EcError::UnsupportedBackend(b) => {
+                write!(f, "{b} kernels are not supported on this CPU")
+            }
+            EcError::Unrecoverable => f.write_str("surviving shards cannot rebuild the erasures"),
+            EcError::UnalignedShardLength { required } => {
+                write!(f, "shard length must be a multiple of {required}")
+            }
+        }
+    }
+}
+
+impl Error for EcError {}
+
+/// Errors from the safe RAID parity wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaidError {
+    /// Fewer than two source vectors.
+    TooFewSources,
+    /// Vectors do not all have the same length.
+    LengthMismatch,
+    /// Vector length is not a multiple of the required granularity.
+    UnalignedLength { required: usize },
+    /// Vector length does not fit the C `int` length parameter.
+    LengthTooLarge,
+    /// `xor_check` / `pq_check` found the parity inconsistent.
+    CheckFailed,
+    /// Any other non-zero return code from `xor_gen` / `pq_gen`.
+    Unknown(c_int),
+}
+
+impl RaidError {
+    /// Maps a `xor_*` / `pq_*` return code to an error; `0` yields `None`.
+    ///
+    /// The C API only distinguishes pass (0) from fail (non-zero), so the
+    /// caller's context decides between `CheckFailed` and `Unknown`.
+    pub fn from_code(code: c_int) -> Option<Self> {
+        match code {
+            0 => None,
+            other => Some(RaidError::Unknown(other)),
+        }
+    }
+}
+
+impl fmt::Display for RaidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RaidError::TooFewSources => f.write_str("at least two source vectors are required"),
+            RaidError::LengthMismatch => f.write_str("vectors differ in length"),
+            RaidError::UnalignedLength { required } => {
+                write!(f, "vector length must be a multiple of {required}")
+            }
+            RaidError::LengthTooLarge => f.write_str("vector length exceeds i32::MAX"),
+            RaidError::CheckFailed => f.write_str("parity check failed"),
+            RaidError::Unknown(code) => write!(f, "unknown RAID error {code}"),
+        }
+    }
+}
+
+impl Error for RaidError {}
+
+/// Errors from [`GfMatrix`](crate::gf::GfMatrix) operations and the
+/// `gf::vect_*` slice functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GfError {
+    /// The backing data does not hold `rows * cols` elements.
+    DataLength { expected: usize, actual: usize },
+    /// The operands' shapes are incompatible, as `(rows, cols)`.
+    ShapeMismatch {
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    /// Inversion needs a square matrix.
+    NotSquare { rows: usize, cols: usize },
+    /// `gf_invert_matrix` found the matrix singular.
+    Singular,
+    /// A selected row index is past the last row.
+    RowOutOfRange(usize),
+    /// A vector's length differs from the others'.
+    VectorLength { expected: usize, actual: usize },
+    /// The number of coefficients and source vectors differ.
+    VectorCount { expected: usize, actual: usize },
+}
+
+impl fmt::Display for GfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            GfError::DataLength { expected, actual } => {
+                write!(f, "expected {expected} matrix elements, got {actual}")
+            }
+            GfError::ShapeMismatch { left, right } => write!(
+                f,
+                "cannot multiply {}x{} by {}x{}",
+                left.0, left.1, right.0, right.1
+            ),
+            GfError::NotSquare { rows, cols } => write!(f, "{rows}x{cols} matrix is not square"),
+            GfError::Singular => f.write_str("matrix is singular"),
+            GfError::RowOutOfRange(r) => write!(f, "row {r} out of range"),
+            GfError::VectorLength { expected, actual } => {
+                write!(f, "expected {expected}-byte vector, got {actual} bytes")
+            }
+            GfError::VectorCount { expected, actual } => {
+                write!(f, "{expected} coefficients for {actual} source vectors")
+            }
+        }
+    }
+}
+
+impl Error for GfError {}
+
+/// Errors from the erasure-coded container reader and writer.
+#[derive(Debug)]
+pub enum ContainerError {
+    /// The underlying reader or writer failed.
+    Io(std::io::Error),
+    /// The input does not start with the container magic.
+    BadMagic,
+    /// The container was written by an unknown format version.
+    UnsupportedVersion(u8),
+    /// The header fails its own CRC or holds invalid parameters.
+    CorruptHeader,
+    /// Stripe size is zero or above `container::MAX_STRIPE_SIZE`.
+    InvalidStripeSize,
+    /// Setting up the erasure coder failed.
+    Ec(EcError),
+    /// More shards are missing or damaged than parity can rebuild.
+    Unrecoverable { damaged: usize, parity: usize },
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerError::Io(e) => write!(f, "container I/O error: {e}"),
+            ContainerError::BadMagic => f.write_str("not an erasure-coded container"),
+            ContainerError::UnsupportedVersion(v) => {
+                write!(f, "unsupported container version {v}")
+            }
+            ContainerError::CorruptHeader => f.write_str("container header is corrupt"),
+            ContainerError::InvalidStripeSize => write!(
+                f,
+                "stripe size must be between 1 and {}",
+                crate::container::MAX_STRIPE_SIZE
+            ),
+            ContainerError::Ec(e) => write!(f, "container erasure coding: {e}"),
+            ContainerError::Unrecoverable { damaged, parity } => {
+                write!(f, "{damaged} damaged shards exceed {parity} parity shards")
+            }
+        }
+    }
+}
+
+impl Error for ContainerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ContainerError::Io(e) => Some(e),
+            ContainerError::Ec(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ContainerError {
+    fn from(e: std::io::Error) -> Self {
+        ContainerError::Io(e)
+    }
+}
+
+impl From<EcError> for ContainerError {
+    fn from(e: EcError) -> Self {
+        ContainerError::Ec(e)
+    }
+}
+
+/// Errors from the multi-buffer hash managers (`crypto` feature).
+#[cfg(feature = "crypto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashError {
+    /// `HASH_CTX_ERROR_INVALID_FLAGS`: flags do not fit the job's state
+    /// (e.g. `HASH_UPDATE` before `HASH_FIRST`).
+    InvalidFlags,
+    /// `HASH_CTX_ERROR_ALREADY_PROCESSING`: the job is still in a lane.
+    AlreadyProcessing,
+    /// `HASH_CTX_ERROR_ALREADY_COMPLETED`: the job was already finished.
+    AlreadyCompleted,
+    /// Any other non-zero error code.
+    Unknown(c_int),
+}
+
+#[cfg(feature = "crypto")]
+impl HashError {
+    /// Maps a `HASH_CTX_ERROR_*` code to an error; `HASH_CTX_ERROR_NONE`
+    /// yields `None`.
+    pub fn from_code(code: c_int) -> Option<Self> {
+        match code {
+            HASH_CTX_ERROR_NONE => None,
+            HASH_CTX_ERROR_INVALID_FLAGS => Some(HashError::InvalidFlags),
+            HASH_CTX_ERROR_ALREADY_PROCESSING => Some(HashError::AlreadyProcessing),
+            HASH_CTX_ERROR_ALREADY_COMPLETED => Some(HashError::AlreadyCompleted),
+            other => Some(HashError::Unknown(other)),
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            HashError::InvalidFlags => f.write_str("invalid hash job flags"),
+            HashError::AlreadyProcessing => f.write_str("hash job is already processing"),
+            HashError::AlreadyCompleted => f.write_str("hash job is already completed"),
+            HashError::Unknown(code) => write!(f, "unknown hash error {code}"),
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl Error for HashError {}
+
+/// Errors from the AES-XTS and AES-GCM wrappers (`crypto` feature).
+#[cfg(feature = "crypto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesError {
+    /// Input shorter than the mode allows: an XTS sector under one AES
+    /// block, or a sealed GCM message without room for its tag.
+    TooShort { len: usize, min: usize },
+    /// The GCM authentication tag did not match.
+    AuthenticationFailed,
+}
+
+#[cfg(feature = "crypto")]
+impl fmt::Display for AesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            AesError::TooShort { len, min } => {
+                write!(f, "input of {len} bytes is shorter than the minimum {min}")
+            }
+            AesError::AuthenticationFailed => f.write_str("authentication tag mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl Error for AesError {}
+
+/// Errors from the rolling hash and chunker setup (`crypto` feature).
+#[cfg(feature = "crypto")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkError {
+    /// `rolling_hash2_init` rejected the window (zero or over
+    /// `FINGERPRINT_MAX_WINDOW`).
+    InvalidWindow(u32),
+    /// Sizes are not ordered `window <= min <= avg <= max <= u32::MAX`.
+    InvalidSizes { min: usize, avg: usize, max: usize },
+}
+
+#[cfg(feature = "crypto")]
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ChunkError::InvalidWindow(w) => write!(f, "invalid rolling hash window {w}"),
+            ChunkError::InvalidSizes { min, avg, max } => {
+                write!(f, "invalid chunk sizes min={min} avg={avg} max={max}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl Error for ChunkError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_codes_stay_distinct() {
+        // -3 means different things to deflate and inflate.
+        assert_eq!(
+            DeflateError::from_code(-3),
+            Some(DeflateError::InvalidState)
+        );
+        assert_eq!(
+            InflateError::from_code(-3),
+            Some(InflateError::InvalidLookback)
+        );
+    }
+
+    #[test]
+    fn test_code_round_trip() {
+        for code in -10..=0 {
+            if let Some(e) = DeflateError::from_code(code) {
+                assert_eq!(e.code(), code);
+            }
+        }
+        for code in -10..=10 {
+            if let Some(e) = InflateError::from_code(code) {
+                assert_eq!(e.code(), code);
+            }
+        }
+        assert_eq!(DeflateError::from_code(COMP_OK), None);
+        assert_eq!(InflateError::from_code(ISAL_DECOMP_OK), None);
+    }
+}
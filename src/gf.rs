@@ -0,0 +1,936 @@
+//! GF(2^8) arithmetic and encode-matrix generation.
+//!
+//! Matrices are row-major `Vec<u8>`s, as expected by `ec_init_tables` and
+//! `gf_invert_matrix`. [`Gf256`] offers the same field as a value type with
+//! operators, computed from log/exp tables in Rust rather than per-element
+//! FFI calls, and [`GfMatrix`] wraps a matrix with its shape so products,
+//! inverses and row selections are checked.
+//!
+//! [`vect_mul`], [`vect_mad`] and [`vect_dot_prod`] apply constants to whole
+//! byte slices through the SIMD kernels, building the 32-byte lookup tables
+//! themselves; [`GfMulTable`] keeps a table around for reuse.
+
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::os::raw::{c_int, c_uchar};
+
+use crate::cpu::Backend;
+use crate::erasure::MatrixKind;
+use crate::error::GfError;
+use crate::sys;
+
+/// Multiplies two field elements.
+pub fn mul(a: u8, b: u8) -> u8 {
+    unsafe { sys::gf_mul(a, b) }
+}
+
+/// Multiplicative inverse of `a`; `inv(0)` is 0.
+pub fn inv(a: u8) -> u8 {
+    unsafe { sys::gf_inv(a) }
+}
+
+/// Generates an `m x k` systematic Vandermonde (Reed-Solomon) encode matrix.
+///
+/// ISA-L does not guarantee this matrix is MDS for every `(m, k)`.
+pub fn gen_rs_matrix(m: usize, k: usize) -> Vec<u8> {
+    let mut a = vec![0u8; m * k];
+    unsafe { sys::gf_gen_rs_matrix(a.as_mut_ptr(), m as c_int, k as c_int) };
+    a
+}
+
+/// Generates an `m x k` systematic Cauchy encode matrix.
+pub fn gen_cauchy1_matrix(m: usize, k: usize) -> Vec<u8> {
+    let mut a = vec![0u8; m * k];
+    unsafe { sys::gf_gen_cauchy1_matrix(a.as_mut_ptr(), m as c_int, k as c_int) };
+    a
+}
+
+/// Inverts an `n x n` matrix, returning `None` if it is singular.
+///
+/// # Panics
+/// Panics if `matrix.len() != n * n`.
+pub fn invert_matrix(matrix: &[u8], n: usize) -> Option<Vec<u8>> {
+    assert_eq!(matrix.len(), n * n, "matrix must be n x n");
+    // gf_invert_matrix destroys its input.
+    let mut input = matrix.to_vec();
+    let mut output = vec![0u8; n * n];
+    let ret = unsafe { sys::gf_invert_matrix(input.as_mut_ptr(), output.as_mut_ptr(), n as c_int) };
+    if ret == 0 {
+        Some(output)
+    } else {
+        None
+    }
+}
+
+/// Reduction polynomial `x^8 + x^4 + x^3 + x^2 + 1`, the one ISA-L uses.
+pub const POLY: u16 = 0x11d;
+
+/// `EXP[i] = 2^i`, doubled in length so a sum of two logs needs no modulo.
+const EXP: [u8; 510] = {
+    let mut exp = [0u8; 510];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        exp[i + 255] = x as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= POLY;
+        }
+        i += 1;
+    }
+    exp
+};
+
+/// `LOG[x]` is the `i` with `2^i = x`; `LOG[0]` is unused.
+const LOG: [u8; 256] = {
+    let mut log = [0u8; 256];
+    let mut i = 0;
+    while i < 255 {
+        log[EXP[i] as usize] = i as u8;
+        i += 1;
+    }
+    log
+};
+
+/// An element of GF(2^8) under [`POLY`].
+///
+/// Addition and subtraction are XOR, so `-a == a`. Division by zero panics
+/// like integer division; [`checked_div`](Self::checked_div) and
+/// [`inverse`](Self::inverse) return `None` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(transparent)]
+pub struct Gf256(pub u8);
+
+impl Gf256 {
+    pub const ZERO: Gf256 = Gf256(0);
+    pub const ONE: Gf256 = Gf256(1);
+    /// Primitive element whose powers generate every non-zero element.
+    pub const GENERATOR: Gf256 = Gf256(2);
+
+    /// `GENERATOR^n`.
+    pub fn exp(n: u32) -> Gf256 {
+        Gf256(EXP[(n % 255) as usize])
+    }
+
+    /// Discrete logarithm to base [`GENERATOR`](Self::GENERATOR), or `None`
+    /// for zero.
+    pub fn log(self) -> Option<u8> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(LOG[self.0 as usize])
+        }
+    }
+
+    /// `self^e`, with `0^0 = 1`.
+    pub fn pow(self, e: u32) -> Gf256 {
+        match self.log() {
+            None if e == 0 => Gf256::ONE,
+            None => Gf256::ZERO,
+            Some(l) => Gf256(EXP[(l as u64 * e as u64 % 255) as usize]),
+        }
+    }
+
+    /// Multiplicative inverse, or `None` for zero.
+    pub fn inverse(self) -> Option<Gf256> {
+        self.log().map(|l| Gf256(EXP[255 - l as usize]))
+    }
+
+    /// `self / rhs`, or `None` if `rhs` is zero.
+    pub fn checked_div(self, rhs: Gf256) -> Option<Gf256> {
+        rhs.inverse().map(|inv| self * inv)
+    }
+}
+
+impl From<u8> for Gf256 {
+    fn from(x: u8) -> Self {
+        Gf256(x)
+    }
+}
+
+impl From<Gf256> for u8 {
+    fn from(x: Gf256) -> Self {
+        x.0
+    }
+}
+
+impl fmt::Display for Gf256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04x}", self.0)
+    }
+}
+
+impl Add for Gf256 {
+    type Output = Gf256;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Gf256) -> Gf256 {
+        Gf256(self.0 ^ rhs.0)
+    }
+}
+
+impl Sub for Gf256 {
+    type Output = Gf256;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Gf256) -> Gf256 {
+        Gf256(self.0 ^ rhs.0)
+    }
+}
+
+impl Neg for Gf256 {
+    type Output = Gf256;
+
+    fn neg(self) -> Gf256 {
+        self
+    }
+}
+
+impl Mul for Gf256 {
+    type Output = Gf256;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Gf256) -> Gf256 {
+        match (self.log(), rhs.log()) {
+            (Some(a), Some(b)) => Gf256(EXP[a as usize + b as usize]),
+            _ => Gf256::ZERO,
+        }
+    }
+}
+
+impl Div for Gf256 {
+    type Output = Gf256;
+
+    /// # Panics
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: Gf256) -> Gf256 {
+        self.checked_div(rhs).expect("division by zero in GF(2^8)")
+    }
+}
+
+macro_rules! assign_ops {
+    ($($trait:ident $method:ident $op:tt;)*) => {
+        $(
+            impl $trait for Gf256 {
+                fn $method(&mut self, rhs: Gf256) {
+                    *self = *self $op rhs;
+                }
+            }
+        )*
+    };
+}
+
+assign_ops! {
+    AddAssign add_assign +;
+    SubAssign sub_assign -;
+    MulAssign mul_assign *;
+    DivAssign div_assign /;
+}
+
+impl Sum for Gf256 {
+    fn sum<I: Iterator<Item = Gf256>>(iter: I) -> Gf256 {
+        iter.fold(Gf256::ZERO, Add::add)
+    }
+}
+
+impl Product for Gf256 {
+    fn product<I: Iterator<Item = Gf256>>(iter: I) -> Gf256 {
+        iter.fold(Gf256::ONE, Mul::mul)
+    }
+}
+
+/// Row-major matrix over GF(2^8) that knows its shape.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GfMatrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<u8>,
+}
+
+impl GfMatrix {
+    /// All-zero `rows x cols` matrix.
+    pub fn zeros(rows: usize, cols: usize) -> GfMatrix {
+        GfMatrix {
+            rows,
+            cols,
+            data: vec![0; rows * cols],
+        }
+    }
+
+    /// `n x n` identity.
+    pub fn identity(n: usize) -> GfMatrix {
+        let mut m = GfMatrix::zeros(n, n);
+        for i in 0..n {
+            m.data[i * n + i] = 1;
+        }
+        m
+    }
+
+    /// Wraps row-major `data`.
+    ///
+    /// # Errors
+    /// [`GfError::DataLength`] unless `data.len() == rows * cols`.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<u8>) -> Result<GfMatrix, GfError> {
+        if data.len() != rows * cols {
+            return Err(GfError::DataLength {
+                expected: rows * cols,
+                actual: data.len(),
+            });
+        }
+        Ok(GfMatrix { rows, cols, data })
+    }
+
+    /// Vandermonde matrix with row `i` equal to `[1, x, x^2, ...]` for
+    /// `x = Gf256(i)`; any `cols` of its rows are independent.
+    ///
+    /// # Panics
+    /// Panics if `rows > 256`, which would repeat an evaluation point.
+    pub fn vandermonde(rows: usize, cols: usize) -> GfMatrix {
+        assert!(rows <= 256, "at most 256 distinct evaluation points");
+        let mut m = GfMatrix::zeros(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                m.data[i * cols + j] = Gf256(i as u8).pow(j as u32).0;
+            }
+        }
+        m
+    }
+
+    /// Cauchy matrix `1 / (x_i + y_j)` with `x_i = cols + i` and `y_j = j`,
+    /// the parity rows `gf_gen_cauchy1_matrix` uses. Every square submatrix
+    /// is invertible.
+    ///
+    /// # Panics
+    /// Panics if `rows + cols > 256`.
+    pub fn cauchy(rows: usize, cols: usize) -> GfMatrix {
+        assert!(rows + cols <= 256, "rows + cols must not exceed 256");
+        let mut m = GfMatrix::zeros(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                m.data[i * cols + j] = inv(((cols + i) ^ j) as u8);
+            }
+        }
+        m
+    }
+
+    /// The `(k + p) x k` systematic encode matrix ISA-L generates for `kind`:
+    /// the identity on top of `p` parity rows.
+    ///
+    /// # Panics
+    /// Panics if `k + p > 255`.
+    pub fn systematic(k: usize, p: usize, kind: MatrixKind) -> GfMatrix {
+        assert!(k + p <= 255, "k + p must not exceed 255");
+        let mut m = GfMatrix::zeros(k + p, k);
+        kind.generate(&mut m.data, k, p);
+        m
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Row-major elements.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Row `r`.
+    ///
+    /// # Panics
+    /// Panics if `r` is out of range.
+    pub fn row(&self, r: usize) -> &[u8] {
+        &self.data[r * self.cols..(r + 1) * self.cols]
+    }
+
+    /// # Panics
+    /// Panics if `(r, c)` is out of range.
+    pub fn get(&self, r: usize, c: usize) -> Gf256 {
+        assert!(r < self.rows && c < self.cols, "index out of range");
+        Gf256(self.data[r * self.cols + c])
+    }
+
+    /// # Panics
+    /// Panics if `(r, c)` is out of range.
+    pub fn set(&mut self, r: usize, c: usize, x: Gf256) {
+        assert!(r < self.rows && c < self.cols, "index out of range");
+        self.data[r * self.cols + c] = x.0;
+    }
+
+    /// Matrix product `self * rhs`.
+    pub fn mul(&self, rhs: &GfMatrix) -> Result<GfMatrix, GfError> {
+        if self.cols != rhs.rows {
+            return Err(GfError::ShapeMismatch {
+                left: (self.rows, self.cols),
+                right: (rhs.rows, rhs.cols),
+            });
+        }
+        let mut out = GfMatrix::zeros(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for j in 0..rhs.cols {
+                let dot: Gf256 = (0..self.cols).map(|x| self.get(i, x) * rhs.get(x, j)).sum();
+                out.data[i * rhs.cols + j] = dot.0;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Inverse via `gf_invert_matrix`.
+    ///
+    /// # Errors
+    /// [`GfError::NotSquare`] or [`GfError::Singular`].
+    pub fn invert(&self) -> Result<GfMatrix, GfError> {
+        if self.rows != self.cols {
+            return Err(GfError::NotSquare {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
+        let data = invert_matrix(&self.data, self.rows).ok_or(GfError::Singular)?;
+        Ok(GfMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            data,
+        })
+    }
+
+    /// Number of linearly independent rows.
+    pub fn rank(&self) -> usize {
+        let mut m = self.clone();
+        let mut rank = 0;
+        for c in 0..m.cols {
+            let pivot = match (rank..m.rows).find(|&r| m.data[r * m.cols + c] != 0) {
+                Some(r) => r,
+                None => continue,
+            };
+            for x in 0..m.cols {
+                m.data.swap(rank * m.cols + x, pivot * m.cols + x);
+            }
+            let scale = m.get(rank, c).inverse().unwrap();
+            for r in rank + 1..m.rows {
+                let factor = m.get(r, c) * scale;
+                if factor == Gf256::ZERO {
+                    continue;
+                }
+                for x in c..m.cols {
+                    let v = m.get(r, x) - factor * m.get(rank, x);
+                    m.set(r, x, v);
+                }
+            }
+            rank += 1;
+            if rank == m.rows {
+                break;
+            }
+        }
+        rank
+    }
+
+    /// New matrix made of the listed rows, in the given order.
+    ///
+    /// # Errors
+    /// [`GfError::RowOutOfRange`] for an index past the last row.
+    pub fn select_rows(&self, rows: &[usize]) -> Result<GfMatrix, GfError> {
+        let mut data = Vec::with_capacity(rows.len() * self.cols);
+        for &r in rows {
+            if r >= self.rows {
+                return Err(GfError::RowOutOfRange(r));
+            }
+            data.extend_from_slice(self.row(r));
+        }
+        Ok(GfMatrix {
+            rows: rows.len(),
+            cols: self.cols,
+            data,
+        })
+    }
+
+    /// Expands the matrix with `ec_init_tables` into the `g_tbls` that
+    /// `ec_encode_data(len, cols, rows, ...)` takes: 32 bytes per element,
+    /// enough for whichever format the dispatched kernel uses.
+    pub fn to_tables(&self) -> Vec<u8> {
+        let mut tables = vec![0u8; 32 * self.data.len()];
+        unsafe {
+            sys::ec_init_tables(
+                self.cols as c_int,
+                self.rows as c_int,
+                self.data.as_ptr() as *mut u8,
+                tables.as_mut_ptr(),
+            )
+        };
+        tables
+    }
+}
+
+type DotProdFn = unsafe extern "C" fn(c_int, c_int, *mut c_uchar, *mut *mut c_uchar, *mut c_uchar);
+type MadFn = unsafe extern "C" fn(c_int, c_int, c_int, *mut c_uchar, *mut c_uchar, *mut c_uchar);
+
+/// Bytes handed to one SIMD kernel call: a multiple of 64, the longest
+/// minimum length among the kernels, that still fits the C `int`.
+const VECT_CHUNK: usize = c_int::MAX as usize & !63;
+
+/// The 32-byte table `gf_vect_mul_init` builds for multiplying by one
+/// constant.
+///
+/// Building it is cheap but not free; network-coding loops that apply the
+/// same coefficients to many packets can build the tables once and reuse
+/// them. A `&[GfMulTable]` is laid out exactly as `gf_vect_dot_prod`
+/// expects its `gftbls`.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GfMulTable([u8; 32]);
+
+impl GfMulTable {
+    /// Table for multiplying by `c`.
+    pub fn new(c: u8) -> Self {
+        let mut table = [0u8; 32];
+        unsafe { sys::gf_vect_mul_init(c, table.as_mut_ptr()) };
+        GfMulTable(table)
+    }
+
+    /// The constant this table multiplies by.
+    pub fn constant(&self) -> u8 {
+        // The low-nibble half starts c * 0, c * 1, ...
+        self.0[1]
+    }
+
+    /// The raw table bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// `dst = c * src`, element-wise.
+    ///
+    /// # Errors
+    /// [`GfError::VectorLength`] if `dst` is not as long as `src`.
+    pub fn mul(&self, src: &[u8], dst: &mut [u8]) -> Result<(), GfError> {
+        check_len(src.len(), dst.len())?;
+        // gf_vect_mul wants 32-byte aligned buffers and a length that is a
+        // multiple of 32; anything else goes through a one-term dot
+        // product, which is a plain multiply of any length.
+        let aligned = (src.as_ptr() as usize | dst.as_ptr() as usize) & 31 == 0;
+        let (dot_prod, _) = vect_kernels();
+        let mut off = 0;
+        while off < src.len() {
+            let remaining = src.len() - off;
+            if aligned && remaining >= 32 {
+                let n = remaining.min(VECT_CHUNK) & !31;
+                unsafe {
+                    sys::gf_vect_mul(
+                        n as c_int,
+                        self.0.as_ptr() as *mut _,
+                        src[off..].as_ptr() as *mut _,
+                        dst[off..].as_mut_ptr() as *mut _,
+                    )
+                };
+                off += n;
+                continue;
+            }
+            let (kernel, n) = split(dot_prod, sys::gf_vect_dot_prod_base, remaining);
+            let mut s = [src[off..].as_ptr() as *mut c_uchar];
+            unsafe {
+                kernel(
+                    n as c_int,
+                    1,
+                    self.0.as_ptr() as *mut _,
+                    s.as_mut_ptr(),
+                    dst[off..].as_mut_ptr(),
+                )
+            };
+            off += n;
+        }
+        Ok(())
+    }
+
+    /// `dst += c * src`, element-wise.
+    ///
+    /// # Errors
+    /// [`GfError::VectorLength`] if `dst` is not as long as `src`.
+    pub fn mad(&self, src: &[u8], dst: &mut [u8]) -> Result<(), GfError> {
+        check_len(src.len(), dst.len())?;
+        let (_, mad) = vect_kernels();
+        let mut off = 0;
+        while off < src.len() {
+            let (kernel, n) = split(mad, sys::gf_vect_mad_base, src.len() - off);
+            unsafe {
+                kernel(
+                    n as c_int,
+                    1,
+                    0,
+                    self.0.as_ptr() as *mut _,
+                    src[off..].as_ptr() as *mut _,
+                    dst[off..].as_mut_ptr(),
+                )
+            };
+            off += n;
+        }
+        Ok(())
+    }
+
+    /// `dst = sum(tables[i].constant() * srcs[i])`, element-wise. With no
+    /// sources `dst` is zeroed.
+    ///
+    /// # Errors
+    /// [`GfError::VectorCount`] if there is not one table per source, and
+    /// [`GfError::VectorLength`] if any source differs in length from `dst`.
+    pub fn dot_prod(tables: &[GfMulTable], srcs: &[&[u8]], dst: &mut [u8]) -> Result<(), GfError> {
+        if tables.len() != srcs.len() {
+            return Err(GfError::VectorCount {
+                expected: tables.len(),
+                actual: srcs.len(),
+            });
+        }
+        for src in srcs {
+            check_len(dst.len(), src.len())?;
+        }
+        if srcs.is_empty() {
+            dst.fill(0);
+            return Ok(());
+        }
+
+        let (dot_prod, _) = vect_kernels();
+        let mut off = 0;
+        while off < dst.len() {
+            let (kernel, n) = split(dot_prod, sys::gf_vect_dot_prod_base, dst.len() - off);
+            let mut s: Vec<*mut c_uchar> = srcs
+                .iter()
+                .map(|src| src[off..].as_ptr() as *mut _)
+                .collect();
+            unsafe {
+                kernel(
+                    n as c_int,
+                    srcs.len() as c_int,
+                    tables.as_ptr() as *mut _,
+                    s.as_mut_ptr(),
+                    dst[off..].as_mut_ptr(),
+                )
+            };
+            off += n;
+        }
+        Ok(())
+    }
+}
+
+/// `dst = c * src`, element-wise. Builds a [`GfMulTable`] per call.
+///
+/// # Errors
+/// [`GfError::VectorLength`] if `dst` is not as long as `src`.
+pub fn vect_mul(c: u8, src: &[u8], dst: &mut [u8]) -> Result<(), GfError> {
+    GfMulTable::new(c).mul(src, dst)
+}
+
+/// `dst += c * src`, element-wise. Builds a [`GfMulTable`] per call.
+///
+/// # Errors
+/// [`GfError::VectorLength`] if `dst` is not as long as `src`.
+pub fn vect_mad(c: u8, src: &[u8], dst: &mut [u8]) -> Result<(), GfError> {
+    GfMulTable::new(c).mad(src, dst)
+}
+
+/// `dst = sum(coefs[i] * srcs[i])`, element-wise. Builds a [`GfMulTable`]
+/// per coefficient per call.
+///
+/// # Errors
+/// As [`GfMulTable::dot_prod`].
+pub fn vect_dot_prod(coefs: &[u8], srcs: &[&[u8]], dst: &mut [u8]) -> Result<(), GfError> {
+    let tables: Vec<GfMulTable> = coefs.iter().map(|&c| GfMulTable::new(c)).collect();
+    GfMulTable::dot_prod(&tables, srcs, dst)
+}
+
+fn check_len(expected: usize, actual: usize) -> Result<(), GfError> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(GfError::VectorLength { expected, actual })
+    }
+}
+
+/// Picks the kernel for the next `remaining` bytes: the SIMD one for the
+/// largest 64-byte multiple it can take, `_base` for a shorter tail.
+fn split<F>(simd: F, base: F, remaining: usize) -> (F, usize) {
+    if remaining >= 64 {
+        (simd, remaining.min(VECT_CHUNK) & !63)
+    } else {
+        (base, remaining)
+    }
+}
+
+/// The widest supported dot-product and multiply-accumulate kernels that
+/// read `gf_vect_mul_init` tables. The multi-binary entry points may pick
+/// a GFNI kernel, which reads 8-byte tables instead, so they are bypassed.
+fn vect_kernels() -> (DotProdFn, MadFn) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if Backend::Avx512.is_supported() {
+            return (sys::gf_vect_dot_prod_avx512, sys::gf_vect_mad_avx512);
+        }
+        if Backend::Avx2.is_supported() {
+            return (sys::gf_vect_dot_prod_avx2, sys::gf_vect_mad_avx2);
+        }
+        if Backend::Avx.is_supported() {
+            return (sys::gf_vect_dot_prod_avx, sys::gf_vect_mad_avx);
+        }
+        if Backend::Sse.is_supported() {
+            return (sys::gf_vect_dot_prod_sse, sys::gf_vect_mad_sse);
+        }
+    }
+    (sys::gf_vect_dot_prod_base, sys::gf_vect_mad_base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_inv() {
+        for a in 1u8..=255 {
+            assert_eq!(mul(a, inv(a)), 1, "a = {a}");
+        }
+        assert_eq!(mul(0, 77), 0);
+    }
+
+    #[test]
+    fn test_invert_matrix() {
+        let k = 5;
+        let a = gen_cauchy1_matrix(k + 3, k);
+        // Top k rows are the identity, which is its own inverse.
+        assert_eq!(invert_matrix(&a[..k * k], k).unwrap(), &a[..k * k]);
+
+        // Rows 3..k+3 mix identity and parity rows; any k rows are invertible.
+        let parity = &a[3 * k..];
+        let inverse = invert_matrix(parity, k).unwrap();
+        for i in 0..k {
+            for j in 0..k {
+                let dot = (0..k).fold(0, |acc, x| acc ^ mul(parity[i * k + x], inverse[x * k + j]));
+                assert_eq!(dot, (i == j) as u8);
+            }
+        }
+
+        assert_eq!(invert_matrix(&[1, 2, 1, 2], 2), None);
+    }
+
+    #[test]
+    fn test_gf256_matches_isal() {
+        for a in 0..=255u8 {
+            for b in 0..=255u8 {
+                assert_eq!((Gf256(a) * Gf256(b)).0, mul(a, b), "{a} * {b}");
+            }
+            match Gf256(a).inverse() {
+                Some(i) => assert_eq!(i.0, inv(a)),
+                None => assert_eq!(a, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_gf256_ops() {
+        let (a, b) = (Gf256(0x53), Gf256(0xca));
+        assert_eq!(a + b, a - b);
+        assert_eq!(-a, a);
+        assert_eq!(a * b / b, a);
+        assert_eq!(a / a, Gf256::ONE);
+        assert_eq!(a.checked_div(Gf256::ZERO), None);
+        assert_eq!(Gf256::ZERO.inverse(), None);
+
+        let mut c = a;
+        c *= b;
+        c += a;
+        assert_eq!(c, a * b + a);
+
+        assert_eq!(a.pow(0), Gf256::ONE);
+        assert_eq!(Gf256::ZERO.pow(0), Gf256::ONE);
+        assert_eq!(Gf256::ZERO.pow(5), Gf256::ZERO);
+        assert_eq!(a.pow(3), a * a * a);
+        assert_eq!(a.pow(255), Gf256::ONE);
+        assert_eq!([a, b, a].iter().copied().product::<Gf256>(), a * b * a);
+        assert_eq!([a, b, a].iter().copied().sum::<Gf256>(), b);
+    }
+
+    #[test]
+    fn test_gf256_generator() {
+        let mut seen = [false; 256];
+        for n in 0..255 {
+            let x = Gf256::exp(n);
+            assert!(!seen[x.0 as usize], "2^{n} repeats");
+            seen[x.0 as usize] = true;
+            assert_eq!(x.log(), Some(n as u8));
+        }
+        assert_eq!(Gf256::exp(255), Gf256::ONE);
+        assert_eq!(Gf256::GENERATOR.pow(8).0, (POLY & 0xff) as u8);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn test_gf256_div_by_zero() {
+        let _ = Gf256(7) / Gf256::ZERO;
+    }
+
+    #[test]
+    fn test_matrix_constructors() {
+        let (k, p) = (6, 3);
+        let cauchy = GfMatrix::systematic(k, p, MatrixKind::Cauchy);
+        assert_eq!(cauchy.as_slice(), &gen_cauchy1_matrix(k + p, k)[..]);
+        assert_eq!(
+            cauchy.select_rows(&[0, 1, 2, 3, 4, 5]).unwrap(),
+            GfMatrix::identity(k)
+        );
+        assert_eq!(
+            cauchy.select_rows(&[6, 7, 8]).unwrap(),
+            GfMatrix::cauchy(p, k)
+        );
+        assert_eq!(
+            GfMatrix::systematic(k, p, MatrixKind::Vandermonde).as_slice(),
+            &gen_rs_matrix(k + p, k)[..]
+        );
+
+        let v = GfMatrix::vandermonde(5, 3);
+        assert_eq!(v.row(0), &[1, 0, 0]);
+        assert_eq!(v.row(2), &[1, 2, 4]);
+        assert_eq!(v.rank(), 3);
+
+        assert_eq!(
+            GfMatrix::from_vec(2, 2, vec![1, 2, 3]),
+            Err(GfError::DataLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_matrix_mul_invert() {
+        let c = GfMatrix::cauchy(4, 4);
+        let inv = c.invert().unwrap();
+        assert_eq!(c.mul(&inv).unwrap(), GfMatrix::identity(4));
+        assert_eq!(inv.mul(&c).unwrap(), GfMatrix::identity(4));
+
+        let tall = GfMatrix::vandermonde(6, 4);
+        assert_eq!(tall.mul(&GfMatrix::identity(4)).unwrap(), tall);
+        assert_eq!(
+            tall.mul(&tall),
+            Err(GfError::ShapeMismatch {
+                left: (6, 4),
+                right: (6, 4)
+            })
+        );
+        assert_eq!(tall.invert(), Err(GfError::NotSquare { rows: 6, cols: 4 }));
+
+        let singular = GfMatrix::from_vec(2, 2, vec![3, 6, 3, 6]).unwrap();
+        assert_eq!(singular.invert(), Err(GfError::Singular));
+        assert_eq!(singular.rank(), 1);
+        assert_eq!(GfMatrix::zeros(3, 3).rank(), 0);
+        assert_eq!(tall.select_rows(&[6]), Err(GfError::RowOutOfRange(6)));
+    }
+
+    #[test]
+    fn test_matrix_tables_encode() {
+        let (k, p, len) = (4, 2, 64);
+        let parity = GfMatrix::cauchy(p, k);
+        let tables = parity.to_tables();
+        assert_eq!(tables.len(), 32 * k * p);
+
+        let mut data: Vec<Vec<u8>> = (0..k)
+            .map(|i| (0..len).map(|j| (i * 17 + j) as u8).collect())
+            .collect();
+        let mut out = vec![vec![0u8; len]; p];
+        let mut src: Vec<*mut u8> = data.iter_mut().map(|d| d.as_mut_ptr()).collect();
+        let mut dst: Vec<*mut u8> = out.iter_mut().map(|d| d.as_mut_ptr()).collect();
+        unsafe {
+            sys::ec_encode_data(
+                len as c_int,
+                k as c_int,
+                p as c_int,
+                tables.as_ptr() as *mut u8,
+                src.as_mut_ptr(),
+                dst.as_mut_ptr(),
+            )
+        };
+
+        for (r, row) in out.iter().enumerate() {
+            for (col, &byte) in row.iter().enumerate() {
+                let expected: Gf256 = (0..k).map(|i| parity.get(r, i) * Gf256(data[i][col])).sum();
+                assert_eq!(byte, expected.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vect_ops_match_scalar() {
+        let data: Vec<u8> = (0..1100).map(|i| (i * 37 + (i >> 8)) as u8).collect();
+        // Lengths around every kernel minimum, and an odd start offset so
+        // the unaligned paths run too.
+        for len in [0, 1, 31, 32, 33, 63, 64, 65, 127, 128, 1000] {
+            for start in [0, 3] {
+                let src = &data[start..start + len];
+                let other = &data[start + 50..start + 50 + len];
+                for c in [0u8, 1, 2, 0x8e, 255] {
+                    let table = GfMulTable::new(c);
+                    assert_eq!(table.constant(), c);
+
+                    let mut dst = vec![0xaa; len];
+                    table.mul(src, &mut dst).unwrap();
+                    for (d, s) in dst.iter().zip(src) {
+                        assert_eq!(*d, (Gf256(c) * Gf256(*s)).0, "len {len} c {c}");
+                    }
+
+                    let mut acc = other.to_vec();
+                    vect_mad(c, src, &mut acc).unwrap();
+                    for i in 0..len {
+                        assert_eq!(acc[i], (Gf256(other[i]) + Gf256(c) * Gf256(src[i])).0);
+                    }
+                }
+
+                let coefs = [3u8, 0x1d, 200];
+                let srcs = [src, other, &data[start + 90..start + 90 + len]];
+                let mut dst = vec![0xaa; len];
+                vect_dot_prod(&coefs, &srcs, &mut dst).unwrap();
+                for i in 0..len {
+                    let expected: Gf256 = coefs
+                        .iter()
+                        .zip(&srcs)
+                        .map(|(&c, s)| Gf256(c) * Gf256(s[i]))
+                        .sum();
+                    assert_eq!(dst[i], expected.0, "len {len} start {start}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_vect_ops_errors() {
+        let src = [1u8; 40];
+        let mut short = [0u8; 39];
+        let length = Err(GfError::VectorLength {
+            expected: 40,
+            actual: 39,
+        });
+        assert_eq!(vect_mul(5, &src, &mut short), length);
+        assert_eq!(vect_mad(5, &src, &mut short), length);
+        assert_eq!(
+            vect_dot_prod(&[1, 2], &[&src], &mut short),
+            Err(GfError::VectorCount {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            vect_dot_prod(&[1], &[&src], &mut short),
+            Err(GfError::VectorLength {
+                expected: 39,
+                actual: 40
+            })
+        );
+
+        let mut dst = [9u8; 40];
+        vect_dot_prod(&[], &[], &mut dst).unwrap();
+        assert_eq!(dst, [0u8; 40]);
+    }
+}
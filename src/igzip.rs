@@ -0,0 +1,891 @@
+//! Safe deflate/inflate wrappers over the igzip API.
+//!
+//! [`Compressor`] and [`Decompressor`] own a heap-allocated `isal_zstream` /
+//! `inflate_state` (sized from C, see `src/helpers.c`) and, for compression
+//! levels 1-3, the `level_buf` scratch memory ISA-L requires the caller to
+//! provide. Both can be reused across many inputs; [`CompressorPool`] keeps a
+//! set of ready compressors so servers don't reallocate level buffers on
+//! every request.
+//!
+//! With the `bytes` feature, [`Compressor::compress_buf`] and
+//! [`Decompressor::decompress_buf`] read from any `Buf` (including chained
+//! `Bytes` segments) and write into `BufMut` spare capacity directly.
+
+use std::ops::{Deref, DerefMut};
+use std::os::raw::c_int;
+use std::sync::Mutex;
+
+#[cfg(feature = "bytes")]
+use bytes::{Buf, BufMut};
+
+use crate::error::{DeflateError, InflateError};
+use crate::raw::RawState;
+use crate::sys::*;
+
+/// Minimum spare output capacity handed to igzip per call.
+const OUT_CHUNK: usize = 64 * 1024;
+
+/// Default `level_buf` size for a compression level, or `None` if the level
+/// is out of range.
+pub fn default_level_buf_size(level: u32) -> Option<u32> {
+    match level {
+        0 => Some(ISAL_DEF_LVL0_DEFAULT),
+        1 => Some(ISAL_DEF_LVL1_DEFAULT),
+        2 => Some(ISAL_DEF_LVL2_DEFAULT),
+        3 => Some(ISAL_DEF_LVL3_DEFAULT),
+        _ => None,
+    }
+}
+
+/// Minimum `level_buf` size accepted by a compression level, or `None` if the
+/// level is out of range.
+pub fn min_level_buf_size(level: u32) -> Option<u32> {
+    match level {
+        0 => Some(ISAL_DEF_LVL0_MIN),
+        1 => Some(ISAL_DEF_LVL1_MIN),
+        2 => Some(ISAL_DEF_LVL2_MIN),
+        3 => Some(ISAL_DEF_LVL3_MIN),
+        _ => None,
+    }
+}
+
+/// Reusable deflate compressor with its level buffer.
+pub struct Compressor {
+    stream: RawState<isal_zstream>,
+    level_buf: Vec<u8>,
+    level: u32,
+    gzip_flag: c_int,
+}
+
+// SAFETY: the stream only points into buffers owned by `self` or borrowed for
+// the duration of a single `compress_into` call.
+unsafe impl Send for Compressor {}
+
+impl Compressor {
+    /// Creates a raw-deflate compressor with the default level buffer size.
+    pub fn new(level: u32) -> Result<Self, DeflateError> {
+        let size = default_level_buf_size(level).ok_or(DeflateError::InvalidLevel)?;
+        Self::with_level_buf_size(level, size)
+    }
+
+    /// Creates a raw-deflate compressor with an explicit level buffer size
+    /// (e.g. [`ISAL_DEF_LVL1_SMALL`] or [`ISAL_DEF_LVL3_EXTRA_LARGE`]).
+    pub fn with_level_buf_size(level: u32, level_buf_size: u32) -> Result<Self, DeflateError> {
+        let min = min_level_buf_size(level).ok_or(DeflateError::InvalidLevel)?;
+        if level_buf_size < min {
+            return Err(DeflateError::InvalidLevelBuf);
+        }
+
+        let stream = unsafe { RawState::new(isal_rs_zstream_size(), isal_rs_zstream_align()) };
+        unsafe { isal_deflate_init(stream.as_ptr()) };
+
+        Ok(Compressor {
+            stream,
+            level_buf: vec![0u8; level_buf_size as usize],
+            level,
+            gzip_flag: IGZIP_DEFLATE,
+        })
+    }
+
+    /// Compression level (0-3).
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Size of the level buffer owned by this compressor.
+    pub fn level_buf_size(&self) -> usize {
+        self.level_buf.len()
+    }
+
+    /// Selects the output wrapper: one of `IGZIP_DEFLATE`, `IGZIP_GZIP`,
+    /// `IGZIP_GZIP_NO_HDR`, `IGZIP_ZLIB` or `IGZIP_ZLIB_NO_HDR`.
+    pub fn set_format(&mut self, gzip_flag: c_int) {
+        self.gzip_flag = gzip_flag;
+    }
+
+    /// Compresses `input` as a complete stream.
+    pub fn compress(&mut self, input: &[u8]) -> Result<Vec<u8>, DeflateError> {
+        let mut out = Vec::with_capacity(input.len() / 2 + OUT_CHUNK);
+        self.compress_into(input, &mut out)?;
+        Ok(out)
+    }
+
+    /// Compresses `input` as a complete stream, appending to `out`.
+    ///
+    /// Returns the number of bytes appended.
+    pub fn compress_into(
+        &mut self,
+        input: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<usize, DeflateError> {
+        let s = self.stream.as_ptr();
+        let start = out.len();
+        let mut pos = 0;
+
+        unsafe {
+            isal_deflate_reset(s);
+            isal_rs_zstream_set_level(
+                s,
+                self.level,
+                self.level_buf.as_mut_ptr(),
+                self.level_buf.len() as u32,
+            );
+        }
+
+        loop {
+            if pos == 0 || (unsafe { isal_rs_zstream_avail_in(s) } == 0 && pos < input.len()) {
+                let chunk = (input.len() - pos).min(u32::MAX as usize);
+                let end_of_stream = pos + chunk == input.len();
+                unsafe {
+                    isal_rs_zstream_set_in(s, input[pos..].as_ptr() as *mut u8, chunk as u32);
+                    isal_rs_zstream_set_flags(
+                        s,
+                        end_of_stream as u16,
+                        NO_FLUSH as u16,
+                        self.gzip_flag as u16,
+                    );
+                }
+                pos += chunk;
+            }
+
+            if out.capacity() - out.len() < OUT_CHUNK {
+                out.reserve(OUT_CHUNK);
+            }
+            let spare = (out.capacity() - out.len()).min(u32::MAX as usize);
+
+            let ret = unsafe {
+                isal_rs_zstream_set_out(s, out.as_mut_ptr().add(out.len()), spare as u32);
+                let ret = isal_deflate(s);
+                let produced = spare - isal_rs_zstream_avail_out(s) as usize;
+                out.set_len(out.len() + produced);
+                ret
+            };
+
+            if let Err(e) = DeflateError::check(ret) {
+                out.truncate(start);
+                return Err(e);
+            }
+            if unsafe { isal_rs_zstream_finished(s) } != 0 {
+                return Ok(out.len() - start);
+            }
+        }
+    }
+
+    /// Starts a new stream for [`compress_step`](Self::compress_step),
+    /// abandoning any stream in progress.
+    pub fn begin(&mut self) {
+        let s = self.stream.as_ptr();
+        unsafe {
+            isal_deflate_reset(s);
+            isal_rs_zstream_set_level(
+                s,
+                self.level,
+                self.level_buf.as_mut_ptr(),
+                self.level_buf.len() as u32,
+            );
+        }
+    }
+
+    /// Advances the stream started by [`begin`](Self::begin), compressing
+    /// from `input` into `output`. Returns `(consumed, produced)`.
+    ///
+    /// `flush` is `NO_FLUSH`, `SYNC_FLUSH` or `FULL_FLUSH`. Pass
+    /// `end_of_stream` once `input` holds the last of the data, and keep
+    /// calling (with the unconsumed input, if any) until
+    /// [`is_finished`](Self::is_finished). At most `u32::MAX` bytes of
+    /// either buffer are used per call.
+    pub fn compress_step(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        flush: c_int,
+        end_of_stream: bool,
+    ) -> Result<(usize, usize), DeflateError> {
+        unsafe {
+            self.deflate_step(
+                input,
+                output.as_mut_ptr(),
+                output.len(),
+                flush,
+                end_of_stream,
+            )
+        }
+    }
+
+    /// [`compress_step`](Self::compress_step) into `out_len` writable (but
+    /// possibly uninitialised) bytes at `out`.
+    unsafe fn deflate_step(
+        &mut self,
+        input: &[u8],
+        out: *mut u8,
+        out_len: usize,
+        flush: c_int,
+        end_of_stream: bool,
+    ) -> Result<(usize, usize), DeflateError> {
+        let s = self.stream.as_ptr();
+        let avail_in = input.len().min(u32::MAX as usize);
+        let avail_out = out_len.min(u32::MAX as usize);
+        isal_rs_zstream_set_in(s, input.as_ptr() as *mut u8, avail_in as u32);
+        isal_rs_zstream_set_out(s, out, avail_out as u32);
+        isal_rs_zstream_set_flags(
+            s,
+            (end_of_stream && avail_in == input.len()) as u16,
+            flush as u16,
+            self.gzip_flag as u16,
+        );
+        DeflateError::check(isal_deflate(s))?;
+        let consumed = avail_in - isal_rs_zstream_avail_in(s) as usize;
+        let produced = avail_out - isal_rs_zstream_avail_out(s) as usize;
+        Ok((consumed, produced))
+    }
+
+    /// Compresses from `src` into `dst`, advancing both, and returns the
+    /// number of bytes written. Each segment of a chained `Buf` goes
+    /// straight to igzip and output lands directly in `dst`'s spare
+    /// capacity, so nothing is copied in between.
+    ///
+    /// Stops once `src` is drained and the flush (or, with
+    /// `end_of_stream`, the whole stream) is written out, or when `dst` is
+    /// full; call again with more room to continue. A growable `dst` such
+    /// as `BytesMut` only extends itself in small steps, so `reserve` for
+    /// the expected output first. The stream must have been started with
+    /// [`begin`](Self::begin).
+    #[cfg(feature = "bytes")]
+    pub fn compress_buf<B: Buf, M: BufMut>(
+        &mut self,
+        src: &mut B,
+        dst: &mut M,
+        flush: c_int,
+        end_of_stream: bool,
+    ) -> Result<usize, DeflateError> {
+        let mut written = 0;
+        while dst.has_remaining_mut() && !self.is_finished() {
+            let input = src.chunk();
+            // Segment boundaries are not flush points: only the final
+            // segment carries the caller's flush and end of stream.
+            let (flush, end) = if input.len() == src.remaining() {
+                (flush, end_of_stream)
+            } else {
+                (NO_FLUSH, false)
+            };
+            let out = dst.chunk_mut();
+            let out_len = out.len();
+            let (consumed, produced) =
+                unsafe { self.deflate_step(input, out.as_mut_ptr(), out_len, flush, end)? };
+            src.advance(consumed);
+            unsafe { dst.advance_mut(produced) };
+            written += produced;
+            let drained = !src.has_remaining() && produced < out_len;
+            if (drained && !end_of_stream) || (consumed == 0 && produced == 0) {
+                break;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Whether the stream has been completely written out.
+    pub fn is_finished(&self) -> bool {
+        unsafe { isal_rs_zstream_finished(self.stream.as_ptr()) != 0 }
+    }
+
+    /// Compresses `input` as a complete stream with a single
+    /// `isal_deflate_stateless` call. No history is carried between calls,
+    /// so independent blocks can be compressed in any order or on any
+    /// thread.
+    ///
+    /// # Errors
+    /// [`DeflateError::InvalidParam`] if `input` exceeds `u32::MAX` bytes.
+    pub fn compress_stateless(&mut self, input: &[u8]) -> Result<Vec<u8>, DeflateError> {
+        if input.len() > u32::MAX as usize {
+            return Err(DeflateError::InvalidParam);
+        }
+        let s = self.stream.as_ptr();
+        // Stored blocks cost 5 bytes per 64 KiB plus the wrapper; start a
+        // little above that and grow on overflow.
+        let mut cap = input.len() + input.len() / 1024 + 1024;
+        loop {
+            let mut out = Vec::with_capacity(cap);
+            let avail = cap.min(u32::MAX as usize);
+            let (ret, produced) = unsafe {
+                isal_deflate_stateless_init(s);
+                isal_rs_zstream_set_level(
+                    s,
+                    self.level,
+                    self.level_buf.as_mut_ptr(),
+                    self.level_buf.len() as u32,
+                );
+                isal_rs_zstream_set_in(s, input.as_ptr() as *mut u8, input.len() as u32);
+                isal_rs_zstream_set_flags(s, 1, NO_FLUSH as u16, self.gzip_flag as u16);
+                isal_rs_zstream_set_out(s, out.as_mut_ptr(), avail as u32);
+                let ret = isal_deflate_stateless(s);
+                let produced = avail - isal_rs_zstream_avail_out(s) as usize;
+                // Leave the stream ready for compress_into again.
+                isal_deflate_init(s);
+                (ret, produced)
+            };
+            match DeflateError::check(ret) {
+                Ok(()) => {
+                    unsafe { out.set_len(produced) };
+                    return Ok(out);
+                }
+                Err(DeflateError::StatelessOverflow) if cap < u32::MAX as usize => cap *= 2,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Reusable inflate decompressor.
+pub struct Decompressor {
+    state: RawState<inflate_state>,
+    crc_flag: u32,
+}
+
+// SAFETY: see `Compressor`.
+unsafe impl Send for Decompressor {}
+
+impl Decompressor {
+    /// Creates a raw-inflate decompressor.
+    pub fn new() -> Self {
+        let state =
+            unsafe { RawState::new(isal_rs_inflate_state_size(), isal_rs_inflate_state_align()) };
+        unsafe { isal_inflate_init(state.as_ptr()) };
+
+        Decompressor {
+            state,
+            crc_flag: ISAL_INFLATE_DEFLATE as u32,
+        }
+    }
+
+    /// Selects the expected input wrapper: one of the `ISAL_INFLATE_*` flags.
+    pub fn set_format(&mut self, crc_flag: c_int) {
+        self.crc_flag = crc_flag as u32;
+    }
+
+    /// Starts a new stream for [`decompress_step`](Self::decompress_step),
+    /// abandoning any stream in progress.
+    pub fn begin(&mut self) {
+        let s = self.state.as_ptr();
+        unsafe {
+            isal_inflate_reset(s);
+            isal_rs_inflate_set_crc_flag(s, self.crc_flag);
+        }
+    }
+
+    /// Advances the stream started by [`begin`](Self::begin), decompressing
+    /// from `input` into `output`. Returns `(consumed, produced)`.
+    ///
+    /// Partial input is buffered internally, so feed data as it arrives
+    /// until [`is_finished`](Self::is_finished); anything after the end of
+    /// the stream is left unconsumed. At most `u32::MAX` bytes of either
+    /// buffer are used per call.
+    pub fn decompress_step(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(usize, usize), InflateError> {
+        unsafe { self.inflate_step(input, output.as_mut_ptr(), output.len()) }
+    }
+
+    /// [`decompress_step`](Self::decompress_step) into `out_len` writable
+    /// (but possibly uninitialised) bytes at `out`.
+    unsafe fn inflate_step(
+        &mut self,
+        input: &[u8],
+        out: *mut u8,
+        out_len: usize,
+    ) -> Result<(usize, usize), InflateError> {
+        let s = self.state.as_ptr();
+        let avail_in = input.len().min(u32::MAX as usize);
+        let avail_out = out_len.min(u32::MAX as usize);
+        isal_rs_inflate_set_in(s, input.as_ptr() as *mut u8, avail_in as u32);
+        isal_rs_inflate_set_out(s, out, avail_out as u32);
+        InflateError::check(isal_inflate(s))?;
+        let consumed = avail_in - isal_rs_inflate_avail_in(s) as usize;
+        let produced = avail_out - isal_rs_inflate_avail_out(s) as usize;
+        Ok((consumed, produced))
+    }
+
+    /// Decompresses from `src` into `dst`, advancing both, and returns the
+    /// number of bytes written, without copying through intermediate
+    /// buffers.
+    ///
+    /// Stops at the end of the stream (leaving anything after it in
+    /// `src`), once `src` is drained and all output it yields is written,
+    /// or when `dst` is full. As with
+    /// [`Compressor::compress_buf`], reserve room in a growable `dst`
+    /// first. The stream must have been started with
+    /// [`begin`](Self::begin).
+    #[cfg(feature = "bytes")]
+    pub fn decompress_buf<B: Buf, M: BufMut>(
+        &mut self,
+        src: &mut B,
+        dst: &mut M,
+    ) -> Result<usize, InflateError> {
+        let mut written = 0;
+        // Step at least once even when `dst` is full: the gzip/zlib trailer
+        // produces no output.
+        while !self.is_finished() {
+            let out = dst.chunk_mut();
+            let out_len = out.len();
+            let (consumed, produced) =
+                unsafe { self.inflate_step(src.chunk(), out.as_mut_ptr(), out_len)? };
+            src.advance(consumed);
+            unsafe { dst.advance_mut(produced) };
+            written += produced;
+            let drained = !src.has_remaining() && produced < out_len;
+            if drained || (consumed == 0 && produced == 0) || !dst.has_remaining_mut() {
+                break;
+            }
+        }
+        Ok(written)
+    }
+
+    /// Whether the final block of the stream has been decoded.
+    pub fn is_finished(&self) -> bool {
+        unsafe { isal_rs_inflate_finished(self.state.as_ptr()) != 0 }
+    }
+
+    /// Decompresses a complete stream.
+    pub fn decompress(&mut self, input: &[u8]) -> Result<Vec<u8>, InflateError> {
+        let mut out = Vec::with_capacity(input.len() * 2 + OUT_CHUNK);
+        self.decompress_into(input, &mut out)?;
+        Ok(out)
+    }
+
+    /// Decompresses a complete stream, appending to `out`.
+    ///
+    /// Returns the number of bytes appended. Input that ends before the final
+    /// block yields [`InflateError::EndOfInput`].
+    pub fn decompress_into(
+        &mut self,
+        input: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<usize, InflateError> {
+        self.decompress_member_into(input, out)
+            .map(|(_, appended)| appended)
+    }
+
+    /// Decompresses the stream at the start of `input`, appending to `out`,
+    /// and leaves anything after it (such as further gzip members) unread.
+    ///
+    /// Returns `(consumed, appended)`: the length of the stream within
+    /// `input` and the number of bytes appended to `out`.
+    pub fn decompress_member_into(
+        &mut self,
+        input: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(usize, usize), InflateError> {
+        let s = self.state.as_ptr();
+        let start = out.len();
+        let mut pos = 0;
+
+        unsafe {
+            isal_inflate_reset(s);
+            isal_rs_inflate_set_crc_flag(s, self.crc_flag);
+        }
+
+        loop {
+            if pos == 0 || (unsafe { isal_rs_inflate_avail_in(s) } == 0 && pos < input.len()) {
+                let chunk = (input.len() - pos).min(u32::MAX as usize);
+                unsafe {
+                    isal_rs_inflate_set_in(s, input[pos..].as_ptr() as *mut u8, chunk as u32)
+                };
+                pos += chunk;
+            }
+
+            if out.capacity() - out.len() < OUT_CHUNK {
+                out.reserve(OUT_CHUNK);
+            }
+            let spare = (out.capacity() - out.len()).min(u32::MAX as usize);
+
+            let (ret, produced) = unsafe {
+                isal_rs_inflate_set_out(s, out.as_mut_ptr().add(out.len()), spare as u32);
+                let ret = isal_inflate(s);
+                let produced = spare - isal_rs_inflate_avail_out(s) as usize;
+                out.set_len(out.len() + produced);
+                (ret, produced)
+            };
+
+            if let Err(e) = InflateError::check(ret) {
+                out.truncate(start);
+                return Err(e);
+            }
+            if unsafe { isal_rs_inflate_finished(s) } != 0 {
+                let consumed = pos - unsafe { isal_rs_inflate_avail_in(s) } as usize;
+                return Ok((consumed, out.len() - start));
+            }
+            if pos == input.len() && unsafe { isal_rs_inflate_avail_in(s) } == 0 && produced < spare
+            {
+                out.truncate(start);
+                return Err(InflateError::EndOfInput);
+            }
+        }
+    }
+}
+
+impl Default for Decompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Thread-safe pool of [`Compressor`]s sharing one level, level buffer size
+/// and output format.
+///
+/// [`CompressorPool::get`] hands out an idle compressor (or builds a new one)
+/// and the guard returns it to the pool on drop, so steady-state request
+/// handling does not allocate.
+pub struct CompressorPool {
+    level: u32,
+    level_buf_size: u32,
+    gzip_flag: c_int,
+    max_idle: usize,
+    idle: Mutex<Vec<Compressor>>,
+}
+
+impl CompressorPool {
+    /// Creates an empty pool for `level` using the default level buffer size.
+    pub fn new(level: u32) -> Result<Self, DeflateError> {
+        let size = default_level_buf_size(level).ok_or(DeflateError::InvalidLevel)?;
+        Self::with_level_buf_size(level, size)
+    }
+
+    /// Creates an empty pool for `level` with an explicit level buffer size.
+    pub fn with_level_buf_size(level: u32, level_buf_size: u32) -> Result<Self, DeflateError> {
+        let min = min_level_buf_size(level).ok_or(DeflateError::InvalidLevel)?;
+        if level_buf_size < min {
+            return Err(DeflateError::InvalidLevelBuf);
+        }
+        Ok(CompressorPool {
+            level,
+            level_buf_size,
+            gzip_flag: IGZIP_DEFLATE,
+            max_idle: usize::MAX,
+            idle: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Sets the output wrapper used by every compressor handed out.
+    pub fn with_format(mut self, gzip_flag: c_int) -> Self {
+        self.gzip_flag = gzip_flag;
+        self
+    }
+
+    /// Caps how many idle compressors are retained; extras are freed on return.
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Takes an idle compressor from the pool, allocating one if none is free.
+    pub fn get(&self) -> PooledCompressor<'_> {
+        let pooled = self.lock().pop();
+        let mut compressor = pooled.unwrap_or_else(|| {
+            Compressor::with_level_buf_size(self.level, self.level_buf_size)
+                .expect("level validated by CompressorPool constructor")
+        });
+        compressor.set_format(self.gzip_flag);
+
+        PooledCompressor {
+            pool: self,
+            compressor: Some(compressor),
+        }
+    }
+
+    /// Number of compressors currently idle in the pool.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Compressor>> {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A [`Compressor`] borrowed from a [`CompressorPool`].
+pub struct PooledCompressor<'a> {
+    pool: &'a CompressorPool,
+    compressor: Option<Compressor>,
+}
+
+impl Deref for PooledCompressor<'_> {
+    type Target = Compressor;
+
+    fn deref(&self) -> &Compressor {
+        self.compressor.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledCompressor<'_> {
+    fn deref_mut(&mut self) -> &mut Compressor {
+        self.compressor.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledCompressor<'_> {
+    fn drop(&mut self) {
+        if let Some(compressor) = self.compressor.take() {
+            let mut idle = self.pool.lock();
+            if idle.len() < self.pool.max_idle {
+                idle.push(compressor);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        (0..200_000u32)
+            .flat_map(|i| format!("line {} of the sample corpus\n", i % 977).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_level_buf_sizes_ordered() {
+        for level in 1..=3 {
+            let min = min_level_buf_size(level).unwrap();
+            let default = default_level_buf_size(level).unwrap();
+            assert!(
+                min < default,
+                "level {level}: min {min} >= default {default}"
+            );
+        }
+        assert_eq!(default_level_buf_size(4), None);
+    }
+
+    #[test]
+    fn test_round_trip_all_levels() {
+        let data = sample();
+        let formats = [
+            (IGZIP_DEFLATE, ISAL_INFLATE_DEFLATE),
+            (IGZIP_GZIP, ISAL_INFLATE_GZIP),
+            (IGZIP_ZLIB, ISAL_INFLATE_ZLIB),
+        ];
+        let mut d = Decompressor::new();
+
+        for level in 0..=ISAL_DEF_MAX_LEVEL as u32 {
+            let mut c = Compressor::new(level).unwrap();
+            for &(comp_fmt, decomp_fmt) in &formats {
+                c.set_format(comp_fmt);
+                d.set_format(decomp_fmt);
+                let compressed = c.compress(&data).unwrap();
+                assert!(compressed.len() < data.len());
+                assert_eq!(d.decompress(&compressed).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stateless_round_trip() {
+        let data = sample();
+        let noise: Vec<u8> = (0..200_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_GZIP);
+
+        for level in 0..=ISAL_DEF_MAX_LEVEL as u32 {
+            let mut c = Compressor::new(level).unwrap();
+            c.set_format(IGZIP_GZIP);
+            for input in [&data[..], &noise[..], &[][..]] {
+                let compressed = c.compress_stateless(input).unwrap();
+                assert_eq!(d.decompress(&compressed).unwrap(), input, "level {level}");
+            }
+            // The stream is still usable for streaming compression.
+            let streamed = c.compress(&data).unwrap();
+            assert_eq!(d.decompress(&streamed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_step_round_trip() {
+        let data = sample();
+        let mut c = Compressor::new(1).unwrap();
+        c.set_format(IGZIP_ZLIB);
+        c.begin();
+        let mut compressed = Vec::new();
+        let mut out = [0u8; 1000];
+        for chunk in data.chunks(4093) {
+            let mut chunk = chunk;
+            while !chunk.is_empty() {
+                let (consumed, produced) =
+                    c.compress_step(chunk, &mut out, NO_FLUSH, false).unwrap();
+                compressed.extend_from_slice(&out[..produced]);
+                chunk = &chunk[consumed..];
+            }
+        }
+        while !c.is_finished() {
+            let (_, produced) = c.compress_step(&[], &mut out, NO_FLUSH, true).unwrap();
+            compressed.extend_from_slice(&out[..produced]);
+        }
+
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_ZLIB);
+        d.begin();
+        let mut decompressed = Vec::new();
+        let mut input = &compressed[..];
+        while !d.is_finished() {
+            let end = input.len().min(777);
+            let (consumed, produced) = d.decompress_step(&input[..end], &mut out).unwrap();
+            decompressed.extend_from_slice(&out[..produced]);
+            input = &input[consumed..];
+        }
+        assert!(input.is_empty());
+        assert_eq!(decompressed, data);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_buf_round_trip() {
+        use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+        let data = sample();
+        let (head, tail) = data.split_at(data.len() / 3);
+        let mut src = Bytes::copy_from_slice(head).chain(Bytes::copy_from_slice(tail));
+
+        let mut c = Compressor::new(2).unwrap();
+        c.set_format(IGZIP_GZIP);
+        c.begin();
+        let mut compressed = BytesMut::with_capacity(data.len());
+        let written = c
+            .compress_buf(&mut src, &mut compressed, NO_FLUSH, true)
+            .unwrap();
+        assert!(c.is_finished());
+        assert!(!src.has_remaining());
+        assert_eq!(written, compressed.len());
+
+        // Splitting the input into segments adds no flush points.
+        let mut flushed = Vec::new();
+        for chained in [false, true] {
+            let mut src: Box<dyn Buf> = if chained {
+                Box::new(Bytes::copy_from_slice(head).chain(Bytes::copy_from_slice(tail)))
+            } else {
+                Box::new(Bytes::copy_from_slice(&data))
+            };
+            c.begin();
+            let mut out = BytesMut::with_capacity(data.len());
+            c.compress_buf(&mut src, &mut out, SYNC_FLUSH, false)
+                .unwrap();
+            flushed.push(out);
+        }
+        assert_eq!(flushed[0], flushed[1]);
+
+        // A trailing frame stays in `src`, and a fixed-size `dst` is
+        // filled across several calls.
+        let mut src = compressed.freeze().chain(&b"next frame"[..]);
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_GZIP);
+        d.begin();
+        let mut out = vec![0u8; data.len()];
+        let mut dst = &mut out[..];
+        while !d.is_finished() {
+            let room = dst.remaining_mut().min(10_000);
+            let mut window = &mut dst[..room];
+            let written = d.decompress_buf(&mut src, &mut window).unwrap();
+            dst = &mut dst[written..];
+        }
+        assert_eq!(dst.len(), 0);
+        assert_eq!(out, data);
+        assert_eq!(src.chunk(), b"next frame");
+    }
+
+    #[test]
+    fn test_concatenated_gzip_members() {
+        let data = sample();
+        let mut c = Compressor::new(1).unwrap();
+        c.set_format(IGZIP_GZIP);
+        let first = c.compress(&data[..1000]).unwrap();
+        let mut stream = first.clone();
+        stream.extend(c.compress(&data[1000..]).unwrap());
+
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_GZIP);
+        let mut out = Vec::new();
+        let (consumed, appended) = d.decompress_member_into(&stream, &mut out).unwrap();
+        assert_eq!((consumed, appended), (first.len(), 1000));
+        d.decompress_into(&stream[consumed..], &mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let mut c = Compressor::new(1).unwrap();
+        let compressed = c.compress(&[]).unwrap();
+        assert!(Decompressor::new()
+            .decompress(&compressed)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_invalid_level() {
+        assert_eq!(Compressor::new(4).err(), Some(DeflateError::InvalidLevel));
+        assert_eq!(
+            Compressor::with_level_buf_size(2, ISAL_DEF_LVL2_MIN - 1).err(),
+            Some(DeflateError::InvalidLevelBuf)
+        );
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let data = sample();
+        let compressed = Compressor::new(1).unwrap().compress(&data).unwrap();
+        let err = Decompressor::new()
+            .decompress(&compressed[..compressed.len() / 2])
+            .unwrap_err();
+        assert_eq!(err, InflateError::EndOfInput);
+    }
+
+    #[test]
+    fn test_pool_reuses_compressors() {
+        let pool = CompressorPool::new(2).unwrap().with_format(IGZIP_GZIP);
+        assert_eq!(pool.idle(), 0);
+        {
+            let mut c = pool.get();
+            assert_eq!(c.level_buf_size(), ISAL_DEF_LVL2_DEFAULT as usize);
+            c.compress(b"hello").unwrap();
+        }
+        assert_eq!(pool.idle(), 1);
+        let _a = pool.get();
+        let _b = pool.get();
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_pool_max_idle() {
+        let pool = CompressorPool::new(1).unwrap().with_max_idle(1);
+        let a = pool.get();
+        let b = pool.get();
+        drop(a);
+        drop(b);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    fn test_pool_across_threads() {
+        let pool = std::sync::Arc::new(CompressorPool::new(3).unwrap());
+        let data = std::sync::Arc::new(sample());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                let data = data.clone();
+                std::thread::spawn(move || {
+                    let mut d = Decompressor::new();
+                    for _ in 0..8 {
+                        let compressed = pool.get().compress(&data).unwrap();
+                        assert_eq!(d.decompress(&compressed).unwrap(), *data);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert!(pool.idle() <= 4);
+    }
+}
@@ -0,0 +1,366 @@
+//! Local Reconstruction Codes (LRC) in the style of Azure storage.
+//!
+//! The `k` data shards are split into `l` equal local groups, each protected
+//! by one XOR parity (`xor_gen`), and the whole stripe is further protected
+//! by `r` global Reed-Solomon parities (`ec_encode_data`, Cauchy rows). A
+//! single lost shard is rebuilt from its group alone, reading `k / l`
+//! shards instead of `k`; larger losses fall back to a global decode that
+//! inverts `k` independent surviving rows with `gf_invert_matrix`.
+//!
+//! Shards are numbered data first, then the `l` local parities in group
+//! order, then the `r` global parities. Any `r + 1` losses are recoverable,
+//! and many patterns of up to `l + r`.
+
+use std::mem;
+use std::os::raw::c_int;
+
+use crate::erasure::{check_count, check_stripe, encode_fn, encode_raw, ErasureCoder};
+use crate::error::EcError;
+use crate::gf::GfMatrix;
+use crate::raid;
+
+/// Largest vector handed to a single `xor_gen` call.
+const MAX_XOR_LEN: usize = c_int::MAX as usize;
+
+/// LRC coder for `k` data shards in `l` local groups plus `r` global
+/// parities.
+#[derive(Clone)]
+pub struct LrcCoder {
+    k: usize,
+    l: usize,
+    r: usize,
+    /// Encodes the global parities.
+    global: ErasureCoder,
+    /// `(k + l + r) x k` generator: identity, group indicators, then the
+    /// global parity rows.
+    generator: GfMatrix,
+}
+
+impl LrcCoder {
+    /// Creates a coder with `k / l` data shards per local group.
+    ///
+    /// # Errors
+    /// [`EcError::InvalidParameters`] (with `p = l + r`) if any count is
+    /// zero, `l` does not divide `k`, or `k + l + r` exceeds 255.
+    pub fn new(k: usize, l: usize, r: usize) -> Result<Self, EcError> {
+        let p = l + r;
+        if k == 0 || l == 0 || r == 0 || !k.is_multiple_of(l) || k + p > 255 {
+            return Err(EcError::InvalidParameters { k, p });
+        }
+
+        let global = ErasureCoder::new(k, r)?;
+        let group_size = k / l;
+        let mut rows = Vec::with_capacity((k + p) * k);
+        rows.extend_from_slice(&global.matrix()[..k * k]);
+        for g in 0..l {
+            rows.extend((0..k).map(|j| (j / group_size == g) as u8));
+        }
+        rows.extend_from_slice(&global.matrix()[k * k..]);
+        let generator = GfMatrix::from_vec(k + p, k, rows).expect("generator shape");
+
+        Ok(LrcCoder {
+            k,
+            l,
+            r,
+            global,
+            generator,
+        })
+    }
+
+    /// Number of data shards.
+    pub fn data_shards(&self) -> usize {
+        self.k
+    }
+
+    /// Number of local groups, and so of local parities.
+    pub fn local_groups(&self) -> usize {
+        self.l
+    }
+
+    /// Number of global parities.
+    pub fn global_parities(&self) -> usize {
+        self.r
+    }
+
+    /// Total number of shards, `k + l + r`.
+    pub fn total_shards(&self) -> usize {
+        self.k + self.l + self.r
+    }
+
+    /// The `(k + l + r) x k` generator matrix.
+    pub fn generator(&self) -> &GfMatrix {
+        &self.generator
+    }
+
+    /// Local group of shard `i`, or `None` for a global parity or an index
+    /// past the last shard.
+    pub fn group_of(&self, i: usize) -> Option<usize> {
+        if i < self.k {
+            Some(i / (self.k / self.l))
+        } else if i < self.k + self.l {
+            Some(i - self.k)
+        } else {
+            None
+        }
+    }
+
+    /// Shards in local group `g`: its data shards, then its local parity.
+    ///
+    /// # Panics
+    /// Panics if `g >= l`.
+    pub fn group_members(&self, g: usize) -> Vec<usize> {
+        assert!(g < self.l, "group {g} out of range");
+        let size = self.k / self.l;
+        let mut members: Vec<usize> = (g * size..(g + 1) * size).collect();
+        members.push(self.k + g);
+        members
+    }
+
+    /// Computes the `l` local parities followed by the `r` global parities
+    /// from the `k` data shards.
+    pub fn encode(&self, data: &[&[u8]], parity: &mut [&mut [u8]]) -> Result<(), EcError> {
+        check_stripe(self.k, self.l + self.r, data, parity)?;
+
+        let (local, global) = parity.split_at_mut(self.l);
+        for (chunk, out) in data.chunks(self.k / self.l).zip(local) {
+            xor_into(chunk, out);
+        }
+        self.global.encode(data, global)
+    }
+
+    /// Rebuilds the shards listed in `erasures` in place.
+    ///
+    /// `shards` holds all `k + l + r` shards in order, each of the same
+    /// length. Groups that lost a single shard are repaired locally first;
+    /// whatever remains is decoded from `k` independent survivors.
+    ///
+    /// # Errors
+    /// [`EcError::Unrecoverable`] if the surviving shards do not determine
+    /// the lost ones.
+    pub fn reconstruct(&self, shards: &mut [&mut [u8]], erasures: &[usize]) -> Result<(), EcError> {
+        let n = self.total_shards();
+        check_count(n, shards.len())?;
+        let len = shards[0].len();
+        if shards.iter().any(|s| s.len() != len) {
+            return Err(EcError::ShardLength);
+        }
+
+        let mut erased = vec![false; n];
+        for &e in erasures {
+            if e >= n || erased[e] {
+                return Err(EcError::InvalidErasure(e));
+            }
+            erased[e] = true;
+        }
+        if erasures.len() > self.l + self.r {
+            return Err(EcError::TooManyErasures {
+                erasures: erasures.len(),
+                parity: self.l + self.r,
+            });
+        }
+
+        for g in 0..self.l {
+            let members = self.group_members(g);
+            let lost: Vec<usize> = members.iter().copied().filter(|&i| erased[i]).collect();
+            if let [e] = lost[..] {
+                let dst = mem::take(&mut shards[e]);
+                let srcs: Vec<&[u8]> = members
+                    .iter()
+                    .filter(|&&i| i != e)
+                    .map(|&i| &*shards[i])
+                    .collect();
+                xor_into(&srcs, dst);
+                shards[e] = dst;
+                erased[e] = false;
+            }
+        }
+
+        let remaining: Vec<usize> = (0..n).filter(|&i| erased[i]).collect();
+        if remaining.is_empty() {
+            return Ok(());
+        }
+        self.global_decode(shards, &erased, &remaining)
+    }
+
+    /// Rebuilds `remaining` from the first `k` linearly independent
+    /// survivors.
+    fn global_decode(
+        &self,
+        shards: &mut [&mut [u8]],
+        erased: &[bool],
+        remaining: &[usize],
+    ) -> Result<(), EcError> {
+        let k = self.k;
+        let mut survivors = Vec::with_capacity(k);
+        for i in (0..erased.len()).filter(|&i| !erased[i]) {
+            survivors.push(i);
+            let rows = self
+                .generator
+                .select_rows(&survivors)
+                .expect("survivor rows");
+            if rows.rank() < survivors.len() {
+                survivors.pop();
+            } else if survivors.len() == k {
+                break;
+            }
+        }
+        if survivors.len() < k {
+            return Err(EcError::Unrecoverable);
+        }
+
+        let inverse = self
+            .generator
+            .select_rows(&survivors)
+            .and_then(|m| m.invert())
+            .map_err(|_| EcError::SingularMatrix)?;
+        let decode = self
+            .generator
+            .select_rows(remaining)
+            .and_then(|m| m.mul(&inverse))
+            .expect("decode shape");
+        let tables = decode.to_tables();
+
+        let len = shards[0].len();
+        let ptrs: Vec<*mut u8> = shards.iter_mut().map(|s| s.as_mut_ptr()).collect();
+        let mut src: Vec<*mut u8> = survivors.iter().map(|&i| ptrs[i]).collect();
+        let mut dst: Vec<*mut u8> = remaining.iter().map(|&i| ptrs[i]).collect();
+        unsafe {
+            encode_raw(
+                encode_fn(None),
+                len,
+                k,
+                remaining.len(),
+                &tables,
+                &mut src,
+                &mut dst,
+            )
+        };
+        Ok(())
+    }
+}
+
+/// Writes the XOR of `srcs` into `dst`; a single source is copied.
+fn xor_into(srcs: &[&[u8]], dst: &mut [u8]) {
+    if let [src] = srcs {
+        dst.copy_from_slice(src);
+        return;
+    }
+    let mut off = 0;
+    while off < dst.len() {
+        let end = dst.len().min(off + MAX_XOR_LEN);
+        let chunk: Vec<&[u8]> = srcs.iter().map(|s| &s[off..end]).collect();
+        raid::xor_gen(&chunk, &mut dst[off..end]).expect("lengths checked");
+        off = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::erasure::{for_each_subset, test_stripe};
+
+    fn stripe(coder: &LrcCoder, len: usize) -> Vec<Vec<u8>> {
+        test_stripe(coder.data_shards(), coder.total_shards(), len, |d, p| {
+            coder.encode(d, p)
+        })
+    }
+
+    #[test]
+    fn test_layout() {
+        let coder = LrcCoder::new(6, 2, 2).unwrap();
+        assert_eq!(coder.total_shards(), 10);
+        assert_eq!(coder.group_members(1), vec![3, 4, 5, 7]);
+        assert_eq!(coder.group_of(4), Some(1));
+        assert_eq!(coder.group_of(6), Some(0));
+        assert_eq!(coder.group_of(8), None);
+
+        let shards = stripe(&coder, 100);
+        let xor: Vec<u8> = (0..100)
+            .map(|j| shards[0][j] ^ shards[1][j] ^ shards[2][j])
+            .collect();
+        assert_eq!(shards[6], xor);
+    }
+
+    #[test]
+    fn test_reconstruct_up_to_r_plus_one() {
+        let coder = LrcCoder::new(6, 2, 2).unwrap();
+        let original = stripe(&coder, 333);
+        for lost in 1..=3 {
+            for_each_subset(coder.total_shards(), lost, |erasures| {
+                let mut shards = original.clone();
+                for &e in erasures {
+                    shards[e].fill(0);
+                }
+                let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+                coder.reconstruct(&mut refs, erasures).unwrap();
+                assert_eq!(shards, original, "erasures {erasures:?}");
+                true
+            });
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_beyond_r_plus_one() {
+        let coder = LrcCoder::new(4, 2, 1).unwrap();
+        let original = stripe(&coder, 64);
+
+        // One loss per group plus the global parity: all local.
+        let mut shards = original.clone();
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+        coder.reconstruct(&mut refs, &[0, 3, 6]).unwrap();
+        assert_eq!(shards, original);
+
+        // A whole group plus its local parity is three unknowns in one
+        // group with only the group and global parity to solve them.
+        let mut shards = original.clone();
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+        assert_eq!(
+            coder.reconstruct(&mut refs, &[0, 1, 4]),
+            Err(EcError::Unrecoverable)
+        );
+    }
+
+    #[test]
+    fn test_single_data_shard_groups() {
+        // Groups of one make the local parity a mirror.
+        let coder = LrcCoder::new(3, 3, 1).unwrap();
+        let original = stripe(&coder, 50);
+        assert_eq!(original[3], original[0]);
+
+        let mut shards = original.clone();
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+        coder.reconstruct(&mut refs, &[1, 5]).unwrap();
+        assert_eq!(shards, original);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            LrcCoder::new(6, 4, 2).err(),
+            Some(EcError::InvalidParameters { k: 6, p: 6 })
+        );
+        assert!(LrcCoder::new(6, 2, 0).is_err());
+
+        let coder = LrcCoder::new(4, 2, 2).unwrap();
+        let mut shards = stripe(&coder, 16);
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+        assert_eq!(
+            coder.reconstruct(&mut refs, &[0, 1, 2, 3, 4]),
+            Err(EcError::TooManyErasures {
+                erasures: 5,
+                parity: 4
+            })
+        );
+        assert_eq!(
+            coder.reconstruct(&mut refs, &[2, 2]),
+            Err(EcError::InvalidErasure(2))
+        );
+        assert_eq!(
+            coder.reconstruct(&mut refs[..7], &[0]),
+            Err(EcError::ShardCount {
+                expected: 8,
+                actual: 7
+            })
+        );
+    }
+}
@@ -0,0 +1,702 @@
+//! Multi-buffer hashing over the isa-l_crypto context managers
+//! (`crypto` feature).
+//!
+//! A multi-buffer manager hashes many independent messages at once, one per
+//! SIMD lane. [`MbManager`] owns the `*_HASH_CTX_MGR` and a pool of job
+//! contexts; each submitted message carries a caller-chosen tag, and
+//! completed digests come back tagged, in whatever order the lanes finish.
+//! Long messages can be fed in chunks with [`MbManager::begin`] and
+//! [`MbManager::update`], and [`MbManager::hash_many`] covers the common
+//! batch case.
+//!
+//! [`MhSha1`] and [`MhSha256`] wrap the multi-hash variants, which spread a
+//! single stream across lanes and produce a digest distinct from plain SHA.
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::os::raw::{c_int, c_void};
+
+use crate::error::HashError;
+use crate::raw::RawState;
+use crate::sys::*;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A hash algorithm with an isa-l_crypto multi-buffer context manager.
+///
+/// Implemented by [`Sha1`], [`Sha256`], [`Sha512`], [`Md5`] and [`Sm3`]; the
+/// methods are the raw hooks [`MbManager`] drives and are not meant to be
+/// called directly.
+pub trait MbHash: sealed::Sealed {
+    /// Digest bytes in the algorithm's canonical byte order.
+    type Digest: Copy + AsRef<[u8]> + std::fmt::Debug + PartialEq + Eq + Send;
+    #[doc(hidden)]
+    type Mgr;
+    #[doc(hidden)]
+    type Ctx;
+
+    #[doc(hidden)]
+    fn mgr_layout() -> (usize, usize);
+    #[doc(hidden)]
+    fn ctx_layout() -> (usize, usize);
+    #[doc(hidden)]
+    unsafe fn mgr_init(mgr: *mut Self::Mgr);
+    #[doc(hidden)]
+    unsafe fn ctx_init(ctx: *mut Self::Ctx);
+    #[doc(hidden)]
+    unsafe fn submit(
+        mgr: *mut Self::Mgr,
+        ctx: *mut Self::Ctx,
+        buf: *const c_void,
+        len: u32,
+        flags: c_int,
+    ) -> *mut Self::Ctx;
+    #[doc(hidden)]
+    unsafe fn flush(mgr: *mut Self::Mgr) -> *mut Self::Ctx;
+    #[doc(hidden)]
+    unsafe fn status(ctx: *const Self::Ctx) -> c_int;
+    #[doc(hidden)]
+    unsafe fn error(ctx: *const Self::Ctx) -> c_int;
+    #[doc(hidden)]
+    unsafe fn user_data(ctx: *const Self::Ctx) -> usize;
+    #[doc(hidden)]
+    unsafe fn set_user_data(ctx: *mut Self::Ctx, data: usize);
+    #[doc(hidden)]
+    unsafe fn digest(ctx: *const Self::Ctx) -> Self::Digest;
+}
+
+macro_rules! mb_hash {
+    ($(
+        $(#[$doc:meta])*
+        $name:ident: $ctx:ident, $mgr:ident, [u8; $len:expr], $word:ty, $to_bytes:ident,
+        ($init:ident, $submit:ident, $flush:ident),
+        ($mgr_size:ident, $mgr_align:ident, $ctx_size:ident, $ctx_align:ident, $ctx_init:ident,
+         $status:ident, $error:ident, $digest:ident, $user_data:ident, $set_user_data:ident);
+    )*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy)]
+            pub enum $name {}
+
+            impl sealed::Sealed for $name {}
+
+            impl MbHash for $name {
+                type Digest = [u8; $len];
+                type Mgr = $mgr;
+                type Ctx = $ctx;
+
+                fn mgr_layout() -> (usize, usize) {
+                    unsafe { ($mgr_size(), $mgr_align()) }
+                }
+                fn ctx_layout() -> (usize, usize) {
+                    unsafe { ($ctx_size(), $ctx_align()) }
+                }
+                unsafe fn mgr_init(mgr: *mut $mgr) {
+                    $init(mgr)
+                }
+                unsafe fn ctx_init(ctx: *mut $ctx) {
+                    $ctx_init(ctx)
+                }
+                unsafe fn submit(
+                    mgr: *mut $mgr,
+                    ctx: *mut $ctx,
+                    buf: *const c_void,
+                    len: u32,
+                    flags: c_int,
+                ) -> *mut $ctx {
+                    $submit(mgr, ctx, buf, len, flags)
+                }
+                unsafe fn flush(mgr: *mut $mgr) -> *mut $ctx {
+                    $flush(mgr)
+                }
+                unsafe fn status(ctx: *const $ctx) -> c_int {
+                    $status(ctx)
+                }
+                unsafe fn error(ctx: *const $ctx) -> c_int {
+                    $error(ctx)
+                }
+                unsafe fn user_data(ctx: *const $ctx) -> usize {
+                    $user_data(ctx) as usize
+                }
+                unsafe fn set_user_data(ctx: *mut $ctx, data: usize) {
+                    $set_user_data(ctx, data as *mut c_void)
+                }
+                unsafe fn digest(ctx: *const $ctx) -> [u8; $len] {
+                    const W: usize = std::mem::size_of::<$word>();
+                    let words = $digest(ctx) as *const $word;
+                    let mut out = [0u8; $len];
+                    for (i, chunk) in out.chunks_mut(W).enumerate() {
+                        chunk.copy_from_slice(&words.add(i).read().$to_bytes());
+                    }
+                    out
+                }
+            }
+        )*
+    };
+}
+
+mb_hash! {
+    /// SHA-1 (`sha1_ctx_mgr_*`).
+    Sha1: SHA1_HASH_CTX, SHA1_HASH_CTX_MGR, [u8; 20], u32, to_be_bytes,
+        (sha1_ctx_mgr_init, sha1_ctx_mgr_submit, sha1_ctx_mgr_flush),
+        (isal_rs_sha1_ctx_mgr_size, isal_rs_sha1_ctx_mgr_align, isal_rs_sha1_ctx_size,
+         isal_rs_sha1_ctx_align, isal_rs_sha1_ctx_init, isal_rs_sha1_ctx_status,
+         isal_rs_sha1_ctx_error, isal_rs_sha1_ctx_digest, isal_rs_sha1_ctx_user_data,
+         isal_rs_sha1_ctx_set_user_data);
+    /// SHA-256 (`sha256_ctx_mgr_*`).
+    Sha256: SHA256_HASH_CTX, SHA256_HASH_CTX_MGR, [u8; 32], u32, to_be_bytes,
+        (sha256_ctx_mgr_init, sha256_ctx_mgr_submit, sha256_ctx_mgr_flush),
+        (isal_rs_sha256_ctx_mgr_size, isal_rs_sha256_ctx_mgr_align, isal_rs_sha256_ctx_size,
+         isal_rs_sha256_ctx_align, isal_rs_sha256_ctx_init, isal_rs_sha256_ctx_status,
+         isal_rs_sha256_ctx_error, isal_rs_sha256_ctx_digest, isal_rs_sha256_ctx_user_data,
+         isal_rs_sha256_ctx_set_user_data);
+    /// SHA-512 (`sha512_ctx_mgr_*`).
+    Sha512: SHA512_HASH_CTX, SHA512_HASH_CTX_MGR, [u8; 64], u64, to_be_bytes,
+        (sha512_ctx_mgr_init, sha512_ctx_mgr_submit, sha512_ctx_mgr_flush),
+        (isal_rs_sha512_ctx_mgr_size, isal_rs_sha512_ctx_mgr_align, isal_rs_sha512_ctx_size,
+         isal_rs_sha512_ctx_align, isal_rs_sha512_ctx_init, isal_rs_sha512_ctx_status,
+         isal_rs_sha512_ctx_error, isal_rs_sha512_ctx_digest, isal_rs_sha512_ctx_user_data,
+         isal_rs_sha512_ctx_set_user_data);
+    /// MD5 (`md5_ctx_mgr_*`).
+    Md5: MD5_HASH_CTX, MD5_HASH_CTX_MGR, [u8; 16], u32, to_le_bytes,
+        (md5_ctx_mgr_init, md5_ctx_mgr_submit, md5_ctx_mgr_flush),
+        (isal_rs_md5_ctx_mgr_size, isal_rs_md5_ctx_mgr_align, isal_rs_md5_ctx_size,
+         isal_rs_md5_ctx_align, isal_rs_md5_ctx_init, isal_rs_md5_ctx_status,
+         isal_rs_md5_ctx_error, isal_rs_md5_ctx_digest, isal_rs_md5_ctx_user_data,
+         isal_rs_md5_ctx_set_user_data);
+    /// SM3 (`sm3_ctx_mgr_*`).
+    Sm3: SM3_HASH_CTX, SM3_HASH_CTX_MGR, [u8; 32], u32, to_be_bytes,
+        (sm3_ctx_mgr_init, sm3_ctx_mgr_submit, sm3_ctx_mgr_flush),
+        (isal_rs_sm3_ctx_mgr_size, isal_rs_sm3_ctx_mgr_align, isal_rs_sm3_ctx_size,
+         isal_rs_sm3_ctx_align, isal_rs_sm3_ctx_init, isal_rs_sm3_ctx_status,
+         isal_rs_sm3_ctx_error, isal_rs_sm3_ctx_digest, isal_rs_sm3_ctx_user_data,
+         isal_rs_sm3_ctx_set_user_data);
+}
+
+/// Position of a chunk within its message, for [`MbManager::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashFlag {
+    /// `HASH_FIRST`: starts the message.
+    First,
+    /// `HASH_UPDATE`: continues the message.
+    Update,
+    /// `HASH_LAST`: ends the message and produces its digest.
+    Last,
+    /// `HASH_ENTIRE`: the whole message in one chunk.
+    Entire,
+}
+
+/// Handle to a job started with [`MbManager::begin`].
+///
+/// The handle goes stale once the job's digest has been returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobId {
+    slot: usize,
+    generation: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobState {
+    /// Begun, no chunk submitted yet.
+    Fresh,
+    /// Between chunks; waiting for the next `update`.
+    Idle,
+    /// A chunk is in a lane.
+    Busy,
+    /// Digest returned; the context is on the free list.
+    Free,
+}
+
+struct Slot<C> {
+    ctx: RawState<C>,
+    tag: u64,
+    generation: u32,
+    state: JobState,
+}
+
+/// Multi-buffer job manager for algorithm `H`.
+///
+/// Messages passed to [`submit`](Self::submit) and [`update`](Self::update)
+/// are borrowed for `'a`, since the C manager reads them lazily as lanes
+/// become free. Chunks longer than `u32::MAX` bytes are split internally.
+pub struct MbManager<'a, H: MbHash> {
+    mgr: RawState<H::Mgr>,
+    /// Job contexts; the slot index is stored in each context's `user_data`.
+    slots: Vec<Slot<H::Ctx>>,
+    free: Vec<usize>,
+    /// Digests collected while waiting on a busy job, not yet handed out.
+    done: VecDeque<(u64, H::Digest)>,
+    jobs: usize,
+    _data: PhantomData<&'a [u8]>,
+}
+
+/// Multi-buffer SHA-256 manager.
+pub type Sha256MbManager<'a> = MbManager<'a, Sha256>;
+
+// SAFETY: the manager and contexts are exclusively owned; the borrowed
+// message buffers are `&'a [u8]`, which is `Send`.
+unsafe impl<H: MbHash> Send for MbManager<'_, H> {}
+
+impl<'a, H: MbHash> MbManager<'a, H> {
+    /// Creates an empty manager.
+    pub fn new() -> Self {
+        let (size, align) = H::mgr_layout();
+        let mgr = RawState::new(size, align);
+        unsafe { H::mgr_init(mgr.as_ptr()) };
+
+        MbManager {
+            mgr,
+            slots: Vec::new(),
+            free: Vec::new(),
+            done: VecDeque::new(),
+            jobs: 0,
+            _data: PhantomData,
+        }
+    }
+
+    /// Hashes every message, returning the digests in input order.
+    pub fn hash_many(msgs: &[&[u8]]) -> Vec<H::Digest> {
+        let mut mgr = MbManager::<H>::new();
+        let mut digests: Vec<Option<H::Digest>> = vec![None; msgs.len()];
+        // Flags and job states are managed here, so no error can surface.
+        const MANAGED: &str = "multi-buffer job flags are managed internally";
+        for (i, msg) in msgs.iter().enumerate() {
+            if let Some((tag, d)) = mgr.submit(i as u64, msg).expect(MANAGED) {
+                digests[tag as usize] = Some(d);
+            }
+        }
+        for (tag, d) in mgr.finish().expect(MANAGED) {
+            digests[tag as usize] = Some(d);
+        }
+        digests.into_iter().map(|d| d.expect(MANAGED)).collect()
+    }
+
+    /// Number of begun or submitted jobs whose digest has not been returned.
+    pub fn in_flight(&self) -> usize {
+        self.jobs
+    }
+
+    /// Submits a complete message tagged with `tag`.
+    ///
+    /// Returns a `(tag, digest)` pair if some job (not necessarily this one)
+    /// has finished.
+    pub fn submit(
+        &mut self,
+        tag: u64,
+        data: &'a [u8],
+    ) -> Result<Option<(u64, H::Digest)>, HashError> {
+        let job = self.begin(tag);
+        self.update(job, data, HashFlag::Entire)
+    }
+
+    /// Starts a job tagged with `tag` whose message is fed in chunks through
+    /// [`update`](Self::update), beginning with [`HashFlag::First`].
+    pub fn begin(&mut self, tag: u64) -> JobId {
+        let slot = self.acquire();
+        let s = &mut self.slots[slot];
+        s.tag = tag;
+        s.state = JobState::Fresh;
+        self.jobs += 1;
+        JobId {
+            slot,
+            generation: s.generation,
+        }
+    }
+
+    /// Submits the next chunk of `job`.
+    ///
+    /// If the job's previous chunk is still in a lane, the manager is flushed
+    /// until it comes back; digests finished meanwhile are returned by later
+    /// calls. Returns a `(tag, digest)` pair if some job has finished.
+    ///
+    /// # Errors
+    /// [`HashError::InvalidFlags`] if `flag` does not follow the job's
+    /// previous chunk, and [`HashError::AlreadyCompleted`] if the job's
+    /// digest has already been returned.
+    pub fn update(
+        &mut self,
+        job: JobId,
+        data: &'a [u8],
+        flag: HashFlag,
+    ) -> Result<Option<(u64, H::Digest)>, HashError> {
+        const MAX: usize = u32::MAX as usize;
+        let starts = matches!(flag, HashFlag::First | HashFlag::Entire);
+        let ends = matches!(flag, HashFlag::Last | HashFlag::Entire);
+
+        let pieces = if data.is_empty() {
+            1
+        } else {
+            (data.len() - 1) / MAX + 1
+        };
+        for i in 0..pieces {
+            let piece = &data[(i * MAX).min(data.len())..((i + 1) * MAX).min(data.len())];
+            let flags = match (starts && i == 0, ends && i == pieces - 1) {
+                (true, true) => HASH_ENTIRE,
+                (true, false) => HASH_FIRST,
+                (false, true) => HASH_LAST,
+                (false, false) => HASH_UPDATE,
+            };
+            self.submit_piece(job, piece, flags)?;
+        }
+        Ok(self.done.pop_front())
+    }
+
+    /// Forces in-flight jobs through the lanes until one finishes.
+    ///
+    /// Returns `None` once every submitted message has been returned.
+    pub fn flush(&mut self) -> Result<Option<(u64, H::Digest)>, HashError> {
+        while self.done.is_empty() {
+            let ctx = unsafe { H::flush(self.mgr.as_ptr()) };
+            if !self.collect(ctx)? {
+                break;
+            }
+        }
+        Ok(self.done.pop_front())
+    }
+
+    /// Flushes every in-flight job, returning the remaining digests.
+    pub fn finish(&mut self) -> Result<Vec<(u64, H::Digest)>, HashError> {
+        let mut out = Vec::with_capacity(self.jobs);
+        while let Some(done) = self.flush()? {
+            out.push(done);
+        }
+        Ok(out)
+    }
+
+    fn submit_piece(&mut self, job: JobId, piece: &[u8], flags: c_int) -> Result<(), HashError> {
+        let slot = self.slot(job)?;
+        while self.slots[slot].state == JobState::Busy {
+            let ctx = unsafe { H::flush(self.mgr.as_ptr()) };
+            if !self.collect(ctx)? {
+                return Err(HashError::AlreadyProcessing);
+            }
+        }
+
+        let state = self.slots[slot].state;
+        let valid = if flags & HASH_FIRST != 0 {
+            state == JobState::Fresh
+        } else {
+            state == JobState::Idle
+        };
+        if !valid {
+            return Err(HashError::InvalidFlags);
+        }
+
+        self.slots[slot].state = JobState::Busy;
+        let ctx = unsafe {
+            H::submit(
+                self.mgr.as_ptr(),
+                self.slots[slot].ctx.as_ptr(),
+                piece.as_ptr() as *const c_void,
+                piece.len() as u32,
+                flags,
+            )
+        };
+        self.collect(ctx)?;
+        Ok(())
+    }
+
+    /// Resolves a live handle to its slot.
+    fn slot(&self, job: JobId) -> Result<usize, HashError> {
+        match self.slots.get(job.slot) {
+            Some(s) if s.generation == job.generation && s.state != JobState::Free => Ok(job.slot),
+            _ => Err(HashError::AlreadyCompleted),
+        }
+    }
+
+    /// Takes a free context, allocating one if all are in use.
+    fn acquire(&mut self) -> usize {
+        if let Some(slot) = self.free.pop() {
+            unsafe { H::ctx_init(self.slots[slot].ctx.as_ptr()) };
+            return slot;
+        }
+        let (size, align) = H::ctx_layout();
+        let ctx = RawState::new(size, align);
+        let slot = self.slots.len();
+        unsafe {
+            H::ctx_init(ctx.as_ptr());
+            H::set_user_data(ctx.as_ptr(), slot);
+        }
+        self.slots.push(Slot {
+            ctx,
+            tag: 0,
+            generation: 0,
+            state: JobState::Free,
+        });
+        slot
+    }
+
+    /// Records a context returned by submit/flush: a finished job's digest
+    /// is queued and its slot recycled, a partial job becomes idle. Returns
+    /// `false` if `ctx` is null.
+    fn collect(&mut self, ctx: *mut H::Ctx) -> Result<bool, HashError> {
+        if ctx.is_null() {
+            return Ok(false);
+        }
+        let slot = unsafe { H::user_data(ctx) };
+
+        if let Some(e) = HashError::from_code(unsafe { H::error(ctx) }) {
+            self.release(slot);
+            return Err(e);
+        }
+        if unsafe { H::status(ctx) } & HASH_CTX_STS_COMPLETE != 0 {
+            let digest = unsafe { H::digest(ctx) };
+            self.done.push_back((self.slots[slot].tag, digest));
+            self.release(slot);
+        } else {
+            self.slots[slot].state = JobState::Idle;
+        }
+        Ok(true)
+    }
+
+    fn release(&mut self, slot: usize) {
+        let s = &mut self.slots[slot];
+        s.state = JobState::Free;
+        s.generation = s.generation.wrapping_add(1);
+        self.free.push(slot);
+        self.jobs -= 1;
+    }
+}
+
+impl<H: MbHash> Default for MbManager<'_, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! mh_hash {
+    ($(#[$doc:meta])* $name:ident, $ctx:ident, $len:expr,
+     $size:ident, $align:ident, $init:ident, $update:ident, $finalize:ident) => {
+        $(#[$doc])*
+        pub struct $name {
+            ctx: RawState<$ctx>,
+        }
+
+        // SAFETY: the context holds no pointers outside itself.
+        unsafe impl Send for $name {}
+
+        impl $name {
+            /// Starts a new digest.
+            pub fn new() -> Self {
+                let ctx = unsafe { RawState::new($size(), $align()) };
+                unsafe { $init(ctx.as_ptr()) };
+                $name { ctx }
+            }
+
+            /// Feeds more of the stream.
+            pub fn update(&mut self, data: &[u8]) {
+                for chunk in data.chunks(u32::MAX as usize) {
+                    unsafe {
+                        $update(
+                            self.ctx.as_ptr(),
+                            chunk.as_ptr() as *const c_void,
+                            chunk.len() as u32,
+                        )
+                    };
+                }
+            }
+
+            /// Finishes the stream; digest words are emitted big-endian.
+            pub fn finalize(self) -> [u8; $len] {
+                let mut words = [0u32; $len / 4];
+                unsafe { $finalize(self.ctx.as_ptr(), words.as_mut_ptr() as *mut c_void) };
+                let mut out = [0u8; $len];
+                for (chunk, word) in out.chunks_mut(4).zip(words.iter()) {
+                    chunk.copy_from_slice(&word.to_be_bytes());
+                }
+                out
+            }
+
+            /// One-shot digest of `data`.
+            pub fn digest(data: &[u8]) -> [u8; $len] {
+                let mut h = Self::new();
+                h.update(data);
+                h.finalize()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+mh_hash!(
+    /// Streaming multi-hash SHA-1 (`mh_sha1_*`).
+    MhSha1, mh_sha1_ctx, 20,
+    isal_rs_mh_sha1_ctx_size, isal_rs_mh_sha1_ctx_align,
+    mh_sha1_init, mh_sha1_update, mh_sha1_finalize
+);
+
+mh_hash!(
+    /// Streaming multi-hash SHA-256 (`mh_sha256_*`).
+    MhSha256, mh_sha256_ctx, 32,
+    isal_rs_mh_sha256_ctx_size, isal_rs_mh_sha256_ctx_align,
+    mh_sha256_init, mh_sha256_update, mh_sha256_finalize
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    fn one<H: MbHash>(data: &[u8]) -> String {
+        let mut mgr = MbManager::<H>::new();
+        let mut out = mgr.submit(0, data).unwrap().into_iter().collect::<Vec<_>>();
+        out.extend(mgr.finish().unwrap());
+        assert_eq!(out.len(), 1);
+        hex(out[0].1.as_ref())
+    }
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(one::<Md5>(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            one::<Sha1>(b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            one::<Sha256>(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            one::<Sha512>(b"abc"),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            one::<Sm3>(b"abc"),
+            "66c7f0f462eeedd9d1f2d46bdc10e4e24167c4875cf2f7a2297da02b8f4ba8e0"
+        );
+        assert_eq!(
+            one::<Sha256>(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_many_jobs_tagged() {
+        let msgs: Vec<Vec<u8>> = (0..200u32)
+            .map(|i| (0..i * 13).map(|j| (i ^ j) as u8).collect())
+            .collect();
+
+        let mut mgr = MbManager::<Sha256>::new();
+        let mut digests = vec![None; msgs.len()];
+        for (i, m) in msgs.iter().enumerate() {
+            if let Some((tag, d)) = mgr.submit(i as u64, m).unwrap() {
+                digests[tag as usize] = Some(d);
+            }
+        }
+        for (tag, d) in mgr.finish().unwrap() {
+            digests[tag as usize] = Some(d);
+        }
+        assert_eq!(mgr.in_flight(), 0);
+
+        for (i, m) in msgs.iter().enumerate() {
+            let mut single = MbManager::<Sha256>::new();
+            single.submit(0, m).unwrap();
+            let expected = single.finish().unwrap()[0].1;
+            assert_eq!(digests[i], Some(expected), "message {i}");
+        }
+    }
+
+    #[test]
+    fn test_mh_sha_streaming() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 31) as u8).collect();
+        let mut h = MhSha1::new();
+        for chunk in data.chunks(777) {
+            h.update(chunk);
+        }
+        assert_eq!(h.finalize(), MhSha1::digest(&data));
+
+        let mut h = MhSha256::new();
+        h.update(&data[..5]);
+        h.update(&data[5..]);
+        assert_eq!(h.finalize(), MhSha256::digest(&data));
+        assert_ne!(MhSha256::digest(&data), MhSha256::digest(&data[1..]));
+    }
+
+    #[test]
+    fn test_chunked_jobs() {
+        let msgs: Vec<Vec<u8>> = (0..20u32)
+            .map(|i| (0..i * 1000 + 7).map(|j| (i + j) as u8).collect())
+            .collect();
+        let expected = Sha256MbManager::hash_many(&msgs.iter().map(|m| &m[..]).collect::<Vec<_>>());
+
+        let mut mgr = Sha256MbManager::new();
+        let jobs: Vec<JobId> = (0..msgs.len()).map(|i| mgr.begin(i as u64)).collect();
+        let mut digests = vec![None; msgs.len()];
+        // Interleave the jobs chunk by chunk so earlier chunks are still in
+        // their lanes when the next one arrives.
+        for round in 0..4 {
+            for (job, m) in jobs.iter().zip(&msgs) {
+                let q = m.len() / 4;
+                let (chunk, flag) = match round {
+                    0 => (&m[..q], HashFlag::First),
+                    3 => (&m[3 * q..], HashFlag::Last),
+                    r => (&m[r * q..(r + 1) * q], HashFlag::Update),
+                };
+                if let Some((tag, d)) = mgr.update(*job, chunk, flag).unwrap() {
+                    digests[tag as usize] = Some(d);
+                }
+            }
+        }
+        for (tag, d) in mgr.finish().unwrap() {
+            digests[tag as usize] = Some(d);
+        }
+        assert_eq!(mgr.in_flight(), 0);
+        let digests: Vec<_> = digests.into_iter().map(Option::unwrap).collect();
+        assert_eq!(digests, expected);
+    }
+
+    #[test]
+    fn test_job_flag_errors() {
+        let mut mgr = Sha256MbManager::new();
+        let job = mgr.begin(7);
+        assert_eq!(
+            mgr.update(job, b"abc", HashFlag::Update),
+            Err(HashError::InvalidFlags)
+        );
+        mgr.update(job, b"a", HashFlag::First).unwrap();
+        assert_eq!(
+            mgr.update(job, b"b", HashFlag::First),
+            Err(HashError::InvalidFlags)
+        );
+        mgr.update(job, b"bc", HashFlag::Last).unwrap();
+
+        let out = mgr.finish().unwrap();
+        assert_eq!(out, [(7, Sha256MbManager::hash_many(&[b"abc"])[0])]);
+        assert_eq!(
+            mgr.update(job, b"", HashFlag::Last),
+            Err(HashError::AlreadyCompleted)
+        );
+
+        // A recycled slot does not revive the old handle.
+        let fresh = mgr.begin(8);
+        assert_ne!(fresh, job);
+        assert_eq!(
+            mgr.update(job, b"", HashFlag::First),
+            Err(HashError::AlreadyCompleted)
+        );
+    }
+
+    #[test]
+    fn test_hash_many_order() {
+        let msgs: [&[u8]; 3] = [b"abc", b"", b"abc"];
+        let d = Sha256MbManager::hash_many(&msgs);
+        assert_eq!(d.len(), 3);
+        assert_eq!(d[0], d[2]);
+        assert_eq!(
+            hex(&d[1]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert!(Sha256MbManager::hash_many(&[]).is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+//! Memory utilities from `mem_routines.h`.
+
+use std::os::raw::c_void;
+
+use crate::sys::*;
+
+/// Returns `true` if every byte of `buf` is zero.
+pub fn is_zero(buf: &[u8]) -> bool {
+    unsafe { isal_zero_detect(buf.as_ptr() as *mut c_void, buf.len()) == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_zero() {
+        let mut buf = vec![0u8; 4097];
+        assert!(is_zero(&buf));
+        assert!(is_zero(&[]));
+        buf[4096] = 1;
+        assert!(!is_zero(&buf));
+        assert!(is_zero(&buf[..4096]));
+    }
+}
@@ -0,0 +1,327 @@
+//! Rayon-parallel versions of the bulk operations (`rayon` feature).
+//!
+//! Everything here runs on the current rayon pool, so it composes with
+//! callers' own `par_iter`s and honours `ThreadPool::install`:
+//! - [`crc32`] / [`crc64`]: chunked CRC, stitched together with
+//!   [`crc32_combine`] / [`crc64_combine`]
+//! - [`ec_encode`] / [`ec_reconstruct`]: `ec_encode_data` over column ranges
+//! - [`xor_gen`] / [`pq_gen`]: RAID parity over column ranges
+//! - [`deflate_blocks`]: `isal_deflate_stateless` of independent blocks
+//!
+//! The CRC combine works for any of the [`crc`](crate::crc) functions: it
+//! derives the effect of appending zero bytes from the function itself and
+//! raises that GF(2) matrix to the needed power by squaring, as zlib's
+//! `crc32_combine` does for its one polynomial.
+
+use std::os::raw::c_int;
+
+use rayon::prelude::*;
+
+use crate::erasure::{column_spans, split_columns, ErasureCoder};
+use crate::error::{DeflateError, EcError, RaidError};
+use crate::igzip::CompressorPool;
+use crate::raid;
+
+/// Bytes per task for the parallel CRCs.
+pub const CRC_CHUNK_LEN: usize = 1 << 20;
+
+/// Columns per task for the parallel RAID functions; a multiple of the
+/// 32-byte `pq_gen` granularity so aligned buffers stay aligned.
+pub const RAID_CHUNK_LEN: usize = 256 << 10;
+
+/// Linear map on CRC states: column `i` is the image of bit `i`.
+type Gf2Matrix = Vec<u64>;
+
+fn apply(m: &[u64], mut v: u64) -> u64 {
+    let mut out = 0;
+    let mut i = 0;
+    while v != 0 {
+        if v & 1 != 0 {
+            out ^= m[i];
+        }
+        v >>= 1;
+        i += 1;
+    }
+    out
+}
+
+/// `a` after `b`.
+fn compose(a: &[u64], b: &[u64]) -> Gf2Matrix {
+    b.iter().map(|&col| apply(a, col)).collect()
+}
+
+/// The map taking `f(c, X)` to `f(c, X || [0; len])` minus `f(0, [0; len])`,
+/// i.e. the state advance over `len` zero bytes, for a `width`-bit CRC.
+fn zeros_operator(f: impl Fn(u64, &[u8]) -> u64, width: u32, mut len: u64) -> Gf2Matrix {
+    let zero = f(0, &[0]);
+    let mut byte: Gf2Matrix = (0..width).map(|i| f(1 << i, &[0]) ^ zero).collect();
+    let mut result: Gf2Matrix = (0..width).map(|i| 1 << i).collect();
+    while len != 0 {
+        if len & 1 != 0 {
+            result = compose(&byte, &result);
+        }
+        len >>= 1;
+        if len != 0 {
+            byte = compose(&byte, &byte);
+        }
+    }
+    result
+}
+
+fn combine(f: impl Fn(u64, &[u8]) -> u64, width: u32, crc_a: u64, crc_b: u64, len_b: u64) -> u64 {
+    apply(&zeros_operator(f, width, len_b), crc_a) ^ crc_b
+}
+
+/// CRC of `A || B` from `crc_a = f(init, A)` and `crc_b = f(0, B)`, where
+/// `f` is any 32-bit function from [`crc`](crate::crc) and `len_b` is the
+/// length of `B`.
+pub fn crc32_combine(f: fn(u32, &[u8]) -> u32, crc_a: u32, crc_b: u32, len_b: u64) -> u32 {
+    let g = |c: u64, d: &[u8]| f(c as u32, d) as u64;
+    combine(g, 32, crc_a as u64, crc_b as u64, len_b) as u32
+}
+
+/// 64-bit counterpart of [`crc32_combine`].
+pub fn crc64_combine(f: fn(u64, &[u8]) -> u64, crc_a: u64, crc_b: u64, len_b: u64) -> u64 {
+    combine(f, 64, crc_a, crc_b, len_b)
+}
+
+/// `f(init, data)`, computed over [`CRC_CHUNK_LEN`] chunks in parallel.
+pub fn crc32(f: fn(u32, &[u8]) -> u32, init: u32, data: &[u8]) -> u32 {
+    let g = |c: u64, d: &[u8]| f(c as u32, d) as u64;
+    par_crc(g, 32, init as u64, data) as u32
+}
+
+/// `f(init, data)`, computed over [`CRC_CHUNK_LEN`] chunks in parallel.
+pub fn crc64(f: fn(u64, &[u8]) -> u64, init: u64, data: &[u8]) -> u64 {
+    par_crc(f, 64, init, data)
+}
+
+fn par_crc(f: impl Fn(u64, &[u8]) -> u64 + Sync, width: u32, init: u64, data: &[u8]) -> u64 {
+    if data.len() <= CRC_CHUNK_LEN {
+        return f(init, data);
+    }
+    let parts: Vec<u64> = data.par_chunks(CRC_CHUNK_LEN).map(|c| f(0, c)).collect();
+    // Every chunk but the last has the same length, so one operator serves.
+    let full = zeros_operator(&f, width, CRC_CHUNK_LEN as u64);
+    let last_len = data.len() - (parts.len() - 1) * CRC_CHUNK_LEN;
+    let (last, body) = parts.split_last().expect("at least two chunks");
+    let crc = body
+        .iter()
+        .fold(init, |crc, &part| apply(&full, crc) ^ part);
+    combine(&f, width, crc, *last, last_len as u64)
+}
+
+/// [`ErasureCoder::encode`] with column ranges spread over the rayon pool.
+pub fn ec_encode(
+    coder: &ErasureCoder,
+    data: &[&[u8]],
+    parity: &mut [&mut [u8]],
+) -> Result<(), EcError> {
+    let len = coder.check_shards(data, parity)?;
+    let spans = column_spans(len, rayon::current_num_threads());
+    split_columns(parity, &spans)
+        .into_par_iter()
+        .zip(spans)
+        .for_each(|(mut parity, span)| coder.encode_span(data, span, &mut parity));
+    Ok(())
+}
+
+/// [`ErasureCoder::reconstruct`] with column ranges spread over the rayon
+/// pool.
+pub fn ec_reconstruct(
+    coder: &ErasureCoder,
+    shards: &mut [&mut [u8]],
+    erasures: &[usize],
+) -> Result<(), EcError> {
+    let (survivors, tables) = match coder.decode_plan(shards, erasures)? {
+        Some(plan) => plan,
+        None => return Ok(()),
+    };
+    let spans = column_spans(shards[0].len(), rayon::current_num_threads());
+    split_columns(shards, &spans)
+        .into_par_iter()
+        .for_each(|mut shards| coder.decode_span(&mut shards, &survivors, erasures, &tables));
+    Ok(())
+}
+
+/// [`raid::xor_gen`] over [`RAID_CHUNK_LEN`] column ranges in parallel.
+pub fn xor_gen(sources: &[&[u8]], parity: &mut [u8]) -> Result<(), RaidError> {
+    if parity.len() <= RAID_CHUNK_LEN {
+        return raid::xor_gen(sources, parity);
+    }
+    check_vectors(sources, &[parity.len()])?;
+    parity
+        .par_chunks_mut(RAID_CHUNK_LEN)
+        .enumerate()
+        .try_for_each(|(i, out)| raid::xor_gen(&columns(sources, i, out.len()), out))
+}
+
+/// [`raid::pq_gen`] over [`RAID_CHUNK_LEN`] column ranges in parallel.
+pub fn pq_gen(sources: &[&[u8]], p: &mut [u8], q: &mut [u8]) -> Result<(), RaidError> {
+    if p.len() <= RAID_CHUNK_LEN {
+        return raid::pq_gen(sources, p, q);
+    }
+    check_vectors(sources, &[p.len(), q.len()])?;
+    if !p.len().is_multiple_of(raid::PQ_LEN_MULTIPLE) {
+        return Err(RaidError::UnalignedLength {
+            required: raid::PQ_LEN_MULTIPLE,
+        });
+    }
+    p.par_chunks_mut(RAID_CHUNK_LEN)
+        .zip(q.par_chunks_mut(RAID_CHUNK_LEN))
+        .enumerate()
+        .try_for_each(|(i, (p, q))| raid::pq_gen(&columns(sources, i, p.len()), p, q))
+}
+
+/// The same checks `raid` makes on the whole vectors, so errors do not
+/// depend on how the columns were split.
+fn check_vectors(sources: &[&[u8]], dests: &[usize]) -> Result<(), RaidError> {
+    if sources.len() < 2 {
+        return Err(RaidError::TooFewSources);
+    }
+    let len = dests[0];
+    if sources.iter().any(|s| s.len() != len) || dests.iter().any(|&d| d != len) {
+        return Err(RaidError::LengthMismatch);
+    }
+    Ok(())
+}
+
+/// Chunk `i` of every source, `len` bytes long.
+fn columns<'a>(sources: &[&'a [u8]], i: usize, len: usize) -> Vec<&'a [u8]> {
+    let start = i * RAID_CHUNK_LEN;
+    sources.iter().map(|s| &s[start..start + len]).collect()
+}
+
+/// Compresses each block as an independent stream with
+/// `isal_deflate_stateless`, in parallel, preserving order. `gzip_flag` is
+/// one of the `IGZIP_*` wrappers, as for
+/// [`Compressor::set_format`](crate::igzip::Compressor::set_format).
+///
+/// Compressors come from a [`CompressorPool`], so each worker allocates its
+/// level buffer once.
+pub fn deflate_blocks<B>(
+    blocks: &[B],
+    level: u32,
+    gzip_flag: c_int,
+) -> Result<Vec<Vec<u8>>, DeflateError>
+where
+    B: AsRef<[u8]> + Sync,
+{
+    let pool = CompressorPool::new(level)?.with_format(gzip_flag);
+    blocks
+        .par_iter()
+        .map(|block| pool.get().compress_stateless(block.as_ref()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc;
+    use crate::igzip::Decompressor;
+    use crate::sys::{IGZIP_GZIP, ISAL_INFLATE_GZIP};
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| ((i * 31) ^ (i >> 11)) as u8).collect()
+    }
+
+    #[test]
+    fn test_crc_combine() {
+        let data = sample(10_000);
+        let (a, b) = data.split_at(3_001);
+        let f: fn(u32, &[u8]) -> u32 = crc::crc32_gzip_refl;
+        let combined = crc32_combine(f, f(0, a), f(0, b), b.len() as u64);
+        assert_eq!(combined, f(0, &data));
+
+        let g: fn(u32, &[u8]) -> u32 = crc::crc32_iscsi;
+        let combined = crc32_combine(g, g(!0, a), g(0, b), b.len() as u64);
+        assert_eq!(combined, g(!0, &data));
+
+        for h in [crc::crc64_ecma_refl, crc::crc64_rocksoft_norm] {
+            let combined = crc64_combine(h, h(0, a), h(0, b), b.len() as u64);
+            assert_eq!(combined, h(0, &data));
+        }
+        assert_eq!(crc32_combine(f, 0x1234, f(0, &[]), 0), 0x1234);
+    }
+
+    #[test]
+    fn test_par_crc() {
+        let data = sample(3 * CRC_CHUNK_LEN + 12_345);
+        assert_eq!(
+            crc32(crc::crc32_gzip_refl, 0, &data),
+            crc::crc32_gzip_refl(0, &data)
+        );
+        assert_eq!(crc32(crc::crc32_ieee, 7, &data), crc::crc32_ieee(7, &data));
+        assert_eq!(
+            crc64(crc::crc64_jones_norm, !0, &data),
+            crc::crc64_jones_norm(!0, &data)
+        );
+        assert_eq!(crc32(crc::crc32_gzip_refl, 0, b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_par_ec() {
+        let (k, p, len) = (5, 3, 100_000);
+        let coder = ErasureCoder::new(k, p).unwrap();
+        let data: Vec<Vec<u8>> = (0..k).map(|i| sample(len + i)[i..].to_vec()).collect();
+        let data: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+
+        let mut expected = vec![vec![0u8; len]; p];
+        let mut refs: Vec<&mut [u8]> = expected.iter_mut().map(|d| &mut d[..]).collect();
+        coder.encode(&data, &mut refs).unwrap();
+        let mut parity = vec![vec![0u8; len]; p];
+        let mut refs: Vec<&mut [u8]> = parity.iter_mut().map(|d| &mut d[..]).collect();
+        ec_encode(&coder, &data, &mut refs).unwrap();
+        assert_eq!(parity, expected);
+
+        let mut shards: Vec<Vec<u8>> = data.iter().map(|d| d.to_vec()).chain(parity).collect();
+        let original = shards.clone();
+        shards[1].fill(0);
+        shards[6].fill(0);
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|d| &mut d[..]).collect();
+        ec_reconstruct(&coder, &mut refs, &[1, 6]).unwrap();
+        assert_eq!(shards, original);
+    }
+
+    #[test]
+    fn test_par_raid() {
+        let len = 3 * RAID_CHUNK_LEN + 64;
+        let srcs: Vec<Vec<u8>> = (0..4).map(|i| sample(len + i)[i..].to_vec()).collect();
+        let refs: Vec<&[u8]> = srcs.iter().map(|s| &s[..]).collect();
+
+        let mut parity = vec![0u8; len];
+        xor_gen(&refs, &mut parity).unwrap();
+        raid::xor_check(&refs, &parity).unwrap();
+
+        let (mut p, mut q) = (vec![0u8; len], vec![0u8; len]);
+        pq_gen(&refs, &mut p, &mut q).unwrap();
+        raid::pq_check(&refs, &p, &q).unwrap();
+
+        assert_eq!(
+            pq_gen(&refs, &mut p[..len - 1], &mut q[..len - 1]),
+            Err(RaidError::LengthMismatch)
+        );
+        assert_eq!(
+            xor_gen(&refs[..1], &mut parity),
+            Err(RaidError::TooFewSources)
+        );
+    }
+
+    #[test]
+    fn test_deflate_blocks() {
+        let data = sample(1 << 20);
+        let blocks: Vec<&[u8]> = data.chunks(100_000).collect();
+        let compressed = deflate_blocks(&blocks, 1, IGZIP_GZIP).unwrap();
+        assert_eq!(compressed.len(), blocks.len());
+
+        let mut d = Decompressor::new();
+        d.set_format(ISAL_INFLATE_GZIP);
+        for (block, gz) in blocks.iter().zip(&compressed) {
+            assert_eq!(&d.decompress(gz).unwrap(), block);
+        }
+        assert_eq!(
+            deflate_blocks(&blocks, 9, IGZIP_GZIP).err(),
+            Some(DeflateError::InvalidLevel)
+        );
+    }
+}
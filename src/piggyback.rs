@@ -0,0 +1,302 @@
+//! Piggybacked Reed-Solomon codes (Hitchhiker-style) for cheaper repair.
+//!
+//! Every shard is split into two halves, `a` and `b`, and each half is
+//! Reed-Solomon encoded on its own with `ec_encode_data`. The data shards
+//! are then partitioned into `p - 1` groups, and the `b` half of parity
+//! `j >= 1` additionally carries the sum of group `j - 1`'s `a` halves
+//! (added with `gf_vect_mad`). Parity 0 is left plain.
+//!
+//! A single lost data shard is rebuilt by decoding its `b` half from the
+//! other data `b` halves and parity 0, then peeling its `a` half out of the
+//! piggyback on its group's parity. That reads `k + group size` half
+//! shards instead of `k` whole ones: about 30% less for 10+4.
+//! [`PiggybackCoder::repair_reads`] lists exactly which ranges are read.
+//!
+//! Any `p` losses remain recoverable: the `a` halves decode as plain RS,
+//! after which the piggybacks are known and can be stripped from the `b`
+//! halves.
+
+use std::ops::Range;
+
+use crate::erasure::{check_count, ErasureCoder};
+use crate::error::EcError;
+use crate::gf;
+
+/// Piggybacked RS coder for `k` data shards and `p` parity shards.
+#[derive(Clone)]
+pub struct PiggybackCoder {
+    /// Codes each half-stripe.
+    inner: ErasureCoder,
+    /// Number of piggyback groups, `min(p - 1, k)`.
+    groups: usize,
+}
+
+impl PiggybackCoder {
+    /// Creates a coder over a Cauchy encode matrix.
+    ///
+    /// With `p == 1` there is nothing to piggyback on and the code is plain
+    /// RS on half shards.
+    pub fn new(k: usize, p: usize) -> Result<Self, EcError> {
+        let inner = ErasureCoder::new(k, p)?;
+        Ok(PiggybackCoder {
+            inner,
+            groups: (p - 1).min(k),
+        })
+    }
+
+    /// Number of data shards.
+    pub fn data_shards(&self) -> usize {
+        self.inner.data_shards()
+    }
+
+    /// Number of parity shards.
+    pub fn parity_shards(&self) -> usize {
+        self.inner.parity_shards()
+    }
+
+    /// Data shards whose `a` halves are piggybacked on parity `g + 1`.
+    fn group(&self, g: usize) -> Range<usize> {
+        let k = self.data_shards();
+        g * k / self.groups..(g + 1) * k / self.groups
+    }
+
+    /// Piggyback group of data shard `i`, if it has one.
+    fn group_of(&self, i: usize) -> Option<usize> {
+        (0..self.groups).find(|&g| self.group(g).contains(&i))
+    }
+
+    /// Byte ranges of other shards that [`reconstruct`](Self::reconstruct)
+    /// reads to rebuild `lost` alone, as `(shard, range)` pairs.
+    ///
+    /// For a data shard this is the `b` half of the other data shards and
+    /// of parity 0 and its group's parity, plus the `a` half of the rest of
+    /// its group. A parity shard needs `k` whole shards, as with plain RS.
+    ///
+    /// # Panics
+    /// Panics if `lost` is not a shard index.
+    pub fn repair_reads(&self, lost: usize, shard_len: usize) -> Vec<(usize, Range<usize>)> {
+        let (k, p) = (self.data_shards(), self.parity_shards());
+        assert!(lost < k + p, "shard {lost} out of range");
+        let half = shard_len / 2;
+        match self.group_of(lost) {
+            Some(g) => {
+                let mut reads: Vec<(usize, Range<usize>)> = (0..k)
+                    .filter(|&i| i != lost)
+                    .map(|i| (i, half..shard_len))
+                    .collect();
+                reads.push((k, half..shard_len));
+                reads.push((k + g + 1, half..shard_len));
+                reads.extend(self.group(g).filter(|&i| i != lost).map(|i| (i, 0..half)));
+                reads
+            }
+            None => (0..k + p)
+                .filter(|&i| i != lost)
+                .take(k)
+                .map(|i| (i, 0..shard_len))
+                .collect(),
+        }
+    }
+
+    /// Computes the `p` parity shards from the `k` data shards.
+    ///
+    /// # Errors
+    /// [`EcError::UnalignedShardLength`] if the shard length is odd.
+    pub fn encode(&self, data: &[&[u8]], parity: &mut [&mut [u8]]) -> Result<(), EcError> {
+        let len = self.inner.check_shards(data, parity)?;
+        let half = half_len(len)?;
+
+        let (data_a, data_b): (Vec<&[u8]>, Vec<&[u8]>) =
+            data.iter().map(|d| d.split_at(half)).unzip();
+        let (mut parity_a, mut parity_b): (Vec<&mut [u8]>, Vec<&mut [u8]>) =
+            parity.iter_mut().map(|d| d.split_at_mut(half)).unzip();
+        self.inner.encode(&data_a, &mut parity_a)?;
+        self.inner.encode(&data_b, &mut parity_b)?;
+        for g in 0..self.groups {
+            self.add_piggyback(g, &data_a, parity_b[g + 1]);
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the shards listed in `erasures` in place.
+    ///
+    /// `shards` holds all `k + p` shards in order (data first, then parity).
+    /// When `erasures` is a single data shard only the ranges named by
+    /// [`repair_reads`](Self::repair_reads) are read.
+    ///
+    /// # Errors
+    /// As [`ErasureCoder::reconstruct`], plus
+    /// [`EcError::UnalignedShardLength`] if the shard length is odd.
+    pub fn reconstruct(&self, shards: &mut [&mut [u8]], erasures: &[usize]) -> Result<(), EcError> {
+        let k = self.data_shards();
+        check_count(k + self.parity_shards(), shards.len())?;
+        let len = shards[0].len();
+        if shards.iter().any(|s| s.len() != len) {
+            return Err(EcError::ShardLength);
+        }
+        let half = half_len(len)?;
+
+        let (mut a, mut b): (Vec<&mut [u8]>, Vec<&mut [u8]>) =
+            shards.iter_mut().map(|s| s.split_at_mut(half)).unzip();
+
+        if let [lost] = *erasures {
+            if let Some(g) = self.group_of(lost) {
+                // b_lost from the other data b halves and plain parity 0.
+                self.inner.reconstruct(&mut b, erasures)?;
+
+                // The group parity's b half minus its RS part is the group's
+                // a sum; minus the other members it is a_lost.
+                let coefs = &self.inner.matrix()[(k + g + 1) * k..(k + g + 2) * k];
+                let data_b: Vec<&[u8]> = b[..k].iter().map(|s| &**s).collect();
+                gf::vect_dot_prod(coefs, &data_b, a[lost]).expect("half lengths match");
+                gf::vect_mad(1, b[k + g + 1], a[lost]).expect("half lengths match");
+                for i in self.group(g).filter(|&i| i != lost) {
+                    let (src, dst) = pair(&mut a, i, lost);
+                    gf::vect_mad(1, src, dst).expect("half lengths match");
+                }
+                return Ok(());
+            }
+        }
+
+        // The a halves are plain RS; once they are whole the piggybacks are
+        // known, so strip them, decode the b halves, and put them back on
+        // every piggybacked parity, rebuilt ones included. Stripping an
+        // erased parity is harmless since the decode overwrites it.
+        self.inner.reconstruct(&mut a, erasures)?;
+        let data_a: Vec<&[u8]> = a[..k].iter().map(|s| &**s).collect();
+        for g in 0..self.groups {
+            self.add_piggyback(g, &data_a, b[k + g + 1]);
+        }
+        self.inner.reconstruct(&mut b, erasures)?;
+        for g in 0..self.groups {
+            self.add_piggyback(g, &data_a, b[k + g + 1]);
+        }
+        Ok(())
+    }
+
+    /// Adds (equivalently, removes) group `g`'s `a` sum to `parity_b`.
+    fn add_piggyback(&self, g: usize, data_a: &[&[u8]], parity_b: &mut [u8]) {
+        for i in self.group(g) {
+            gf::vect_mad(1, data_a[i], parity_b).expect("half lengths match");
+        }
+    }
+}
+
+fn half_len(len: usize) -> Result<usize, EcError> {
+    if len & 1 != 0 {
+        return Err(EcError::UnalignedShardLength { required: 2 });
+    }
+    Ok(len / 2)
+}
+
+/// Shared `v[src]` and mutable `v[dst]`, for `src != dst`.
+fn pair<'a>(v: &'a mut [&mut [u8]], src: usize, dst: usize) -> (&'a [u8], &'a mut [u8]) {
+    if src < dst {
+        let (lo, hi) = v.split_at_mut(dst);
+        (&*lo[src], &mut *hi[0])
+    } else {
+        let (lo, hi) = v.split_at_mut(src);
+        (&*hi[0], &mut *lo[dst])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::erasure::test_stripe;
+
+    fn stripe(coder: &PiggybackCoder, len: usize) -> Vec<Vec<u8>> {
+        let k = coder.data_shards();
+        test_stripe(k, k + coder.parity_shards(), len, |d, p| coder.encode(d, p))
+    }
+
+    fn rebuild(coder: &PiggybackCoder, shards: &mut [Vec<u8>], erasures: &[usize]) {
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+        coder.reconstruct(&mut refs, erasures).unwrap();
+    }
+
+    #[test]
+    fn test_a_halves_are_plain_rs() {
+        let (k, p, len) = (4, 3, 200);
+        let coder = PiggybackCoder::new(k, p).unwrap();
+        let shards = stripe(&coder, len);
+
+        let rs = ErasureCoder::new(k, p).unwrap();
+        let data: Vec<&[u8]> = shards[..k].iter().map(|d| &d[..len / 2]).collect();
+        let mut parity = vec![vec![0u8; len / 2]; p];
+        let mut refs: Vec<&mut [u8]> = parity.iter_mut().map(|d| &mut d[..]).collect();
+        rs.encode(&data, &mut refs).unwrap();
+        for j in 0..p {
+            assert_eq!(&shards[k + j][..len / 2], &parity[j][..]);
+        }
+    }
+
+    #[test]
+    fn test_single_repair_reads_only_plan() {
+        let (k, p, len) = (10, 4, 256);
+        let coder = PiggybackCoder::new(k, p).unwrap();
+        let original = stripe(&coder, len);
+
+        for lost in 0..k + p {
+            let reads = coder.repair_reads(lost, len);
+            // Blank everything the plan does not read.
+            let mut shards = vec![vec![0u8; len]; k + p];
+            for (i, range) in &reads {
+                shards[*i][range.clone()].copy_from_slice(&original[*i][range.clone()]);
+            }
+            rebuild(&coder, &mut shards, &[lost]);
+            assert_eq!(shards[lost], original[lost], "lost {lost}");
+
+            let read: usize = reads.iter().map(|(_, r)| r.len()).sum();
+            if lost < k {
+                // Groups of 3, 3 and 4: 13 or 14 halves instead of 10 shards.
+                assert!(read * 10 <= k * len * 7, "lost {lost} read {read}");
+            } else {
+                assert_eq!(read, k * len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_any_p() {
+        let (k, p, len) = (5, 3, 64);
+        let coder = PiggybackCoder::new(k, p).unwrap();
+        let original = stripe(&coder, len);
+        let n = k + p;
+        for mask in 1u32..1 << n {
+            if mask.count_ones() as usize > p {
+                continue;
+            }
+            let erasures: Vec<usize> = (0..n).filter(|&i| mask & 1 << i != 0).collect();
+            let mut shards = original.clone();
+            for &e in &erasures {
+                shards[e].fill(0xee);
+            }
+            rebuild(&coder, &mut shards, &erasures);
+            assert_eq!(shards, original, "erasures {erasures:?}");
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let coder = PiggybackCoder::new(3, 2).unwrap();
+        let data = vec![vec![0u8; 33]; 3];
+        let data: Vec<&[u8]> = data.iter().map(|d| &d[..]).collect();
+        let mut parity = vec![vec![0u8; 33]; 2];
+        let mut refs: Vec<&mut [u8]> = parity.iter_mut().map(|d| &mut d[..]).collect();
+        assert_eq!(
+            coder.encode(&data, &mut refs),
+            Err(EcError::UnalignedShardLength { required: 2 })
+        );
+        assert!(PiggybackCoder::new(3, 0).is_err());
+
+        let mut shards = stripe(&coder, 32);
+        let mut refs: Vec<&mut [u8]> = shards.iter_mut().map(|s| &mut s[..]).collect();
+        assert_eq!(
+            coder.reconstruct(&mut refs, &[0, 1, 2]),
+            Err(EcError::TooManyErasures {
+                erasures: 3,
+                parity: 2
+            })
+        );
+    }
+}
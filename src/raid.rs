@@ -0,0 +1,152 @@
+//! Safe RAID5 (XOR) and RAID6 (P+Q) parity over the `raid.h` functions.
+//!
+//! The multi-binary kernels require every vector to be 32-byte aligned; when
+//! a caller's buffers are not, the wrappers fall back to the `_base` versions
+//! so plain `Vec<u8>` buffers are always accepted.
+
+use std::os::raw::{c_int, c_void};
+
+use crate::error::RaidError;
+use crate::sys::*;
+
+/// Alignment required by the SIMD RAID kernels.
+const SIMD_ALIGN: usize = 32;
+
+/// Length granularity required by `pq_gen` / `pq_check`.
+pub const PQ_LEN_MULTIPLE: usize = 32;
+
+type RaidFn = unsafe extern "C" fn(c_int, c_int, *mut *mut c_void) -> c_int;
+
+/// Validates the vector set and returns the shared length.
+fn check_vectors(sources: &[&[u8]], dests: &[usize]) -> Result<usize, RaidError> {
+    if sources.len() < 2 {
+        return Err(RaidError::TooFewSources);
+    }
+    let len = sources[0].len();
+    if sources.iter().any(|s| s.len() != len) || dests.iter().any(|&d| d != len) {
+        return Err(RaidError::LengthMismatch);
+    }
+    if len > c_int::MAX as usize {
+        return Err(RaidError::LengthTooLarge);
+    }
+    Ok(len)
+}
+
+/// Calls `simd` when every vector is 32-byte aligned, `base` otherwise.
+fn call(simd: RaidFn, base: RaidFn, len: usize, mut array: Vec<*mut c_void>) -> c_int {
+    let aligned = array.iter().all(|p| (*p as usize) & (SIMD_ALIGN - 1) == 0);
+    let f = if aligned { simd } else { base };
+    unsafe { f(array.len() as c_int, len as c_int, array.as_mut_ptr()) }
+}
+
+fn pointers(sources: &[&[u8]]) -> Vec<*mut c_void> {
+    sources.iter().map(|s| s.as_ptr() as *mut c_void).collect()
+}
+
+/// Writes the XOR of `sources` into `parity` (RAID5).
+pub fn xor_gen(sources: &[&[u8]], parity: &mut [u8]) -> Result<(), RaidError> {
+    let len = check_vectors(sources, &[parity.len()])?;
+    let mut array = pointers(sources);
+    array.push(parity.as_mut_ptr() as *mut c_void);
+    let code = call(crate::sys::xor_gen, xor_gen_base, len, array);
+    RaidError::from_code(code).map_or(Ok(()), Err)
+}
+
+/// Checks that `parity` is the XOR of `sources`.
+pub fn xor_check(sources: &[&[u8]], parity: &[u8]) -> Result<(), RaidError> {
+    let len = check_vectors(sources, &[parity.len()])?;
+    let mut array = pointers(sources);
+    array.push(parity.as_ptr() as *mut c_void);
+    match call(crate::sys::xor_check, xor_check_base, len, array) {
+        0 => Ok(()),
+        _ => Err(RaidError::CheckFailed),
+    }
+}
+
+/// Writes the P (XOR) and Q (GF(2^8) syndrome) parity of `sources` (RAID6).
+///
+/// The vector length must be a multiple of [`PQ_LEN_MULTIPLE`].
+pub fn pq_gen(sources: &[&[u8]], p: &mut [u8], q: &mut [u8]) -> Result<(), RaidError> {
+    let len = check_vectors(sources, &[p.len(), q.len()])?;
+    if len % PQ_LEN_MULTIPLE != 0 {
+        return Err(RaidError::UnalignedLength {
+            required: PQ_LEN_MULTIPLE,
+        });
+    }
+    let mut array = pointers(sources);
+    array.push(p.as_mut_ptr() as *mut c_void);
+    array.push(q.as_mut_ptr() as *mut c_void);
+    let code = call(crate::sys::pq_gen, pq_gen_base, len, array);
+    RaidError::from_code(code).map_or(Ok(()), Err)
+}
+
+/// Checks the P and Q parity of `sources`.
+pub fn pq_check(sources: &[&[u8]], p: &[u8], q: &[u8]) -> Result<(), RaidError> {
+    let len = check_vectors(sources, &[p.len(), q.len()])?;
+    if len % PQ_LEN_MULTIPLE != 0 {
+        return Err(RaidError::UnalignedLength {
+            required: PQ_LEN_MULTIPLE,
+        });
+    }
+    let mut array = pointers(sources);
+    array.push(p.as_ptr() as *mut c_void);
+    array.push(q.as_ptr() as *mut c_void);
+    match call(crate::sys::pq_check, pq_check_base, len, array) {
+        0 => Ok(()),
+        _ => Err(RaidError::CheckFailed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(n: usize, len: usize) -> Vec<Vec<u8>> {
+        (0..n)
+            .map(|i| (0..len).map(|j| (i * 13 + j * 3 + 1) as u8).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_xor_gen_check() {
+        let srcs = sources(4, 256);
+        let refs: Vec<&[u8]> = srcs.iter().map(|s| s.as_slice()).collect();
+        let mut parity = vec![0u8; 256];
+        xor_gen(&refs, &mut parity).unwrap();
+
+        for j in 0..256 {
+            assert_eq!(parity[j], srcs.iter().fold(0, |acc, s| acc ^ s[j]));
+        }
+        xor_check(&refs, &parity).unwrap();
+
+        parity[17] ^= 1;
+        assert_eq!(xor_check(&refs, &parity), Err(RaidError::CheckFailed));
+    }
+
+    #[test]
+    fn test_pq_gen_check() {
+        let srcs = sources(5, 512);
+        let refs: Vec<&[u8]> = srcs.iter().map(|s| s.as_slice()).collect();
+        let mut p = vec![0u8; 512];
+        let mut q = vec![0u8; 512];
+        pq_gen(&refs, &mut p, &mut q).unwrap();
+        pq_check(&refs, &p, &q).unwrap();
+
+        q[100] ^= 0x40;
+        assert_eq!(pq_check(&refs, &p, &q), Err(RaidError::CheckFailed));
+    }
+
+    #[test]
+    fn test_errors() {
+        let srcs = sources(3, 40);
+        let refs: Vec<&[u8]> = srcs.iter().map(|s| s.as_slice()).collect();
+        let mut p = vec![0u8; 40];
+        let mut q = vec![0u8; 40];
+        assert_eq!(
+            pq_gen(&refs, &mut p, &mut q),
+            Err(RaidError::UnalignedLength { required: 32 })
+        );
+        assert_eq!(xor_gen(&refs[..1], &mut p), Err(RaidError::TooFewSources));
+        assert_eq!(xor_gen(&refs, &mut p[..39]), Err(RaidError::LengthMismatch));
+    }
+}
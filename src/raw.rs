@@ -0,0 +1,39 @@
+//! Heap storage for the opaque C structs used by the safe wrappers.
+
+use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+use std::ptr::NonNull;
+
+/// Zeroed heap allocation for one of the opaque C structs, sized and aligned
+/// from the `isal_rs_*_size` / `_align` helpers.
+pub(crate) struct RawState<T> {
+    ptr: NonNull<T>,
+    layout: Layout,
+}
+
+impl<T> RawState<T> {
+    pub(crate) fn new(size: usize, align: usize) -> Self {
+        let layout = Layout::from_size_align(size, align).expect("invalid C struct layout");
+        // SAFETY: the C structs are never zero-sized.
+        let ptr = unsafe { alloc_zeroed(layout) } as *mut T;
+        match NonNull::new(ptr) {
+            Some(ptr) => RawState { ptr, layout },
+            None => handle_alloc_error(layout),
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Size of the allocation in bytes.
+    #[cfg(feature = "crypto")]
+    pub(crate) fn size(&self) -> usize {
+        self.layout.size()
+    }
+}
+
+impl<T> Drop for RawState<T> {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr.as_ptr() as *mut u8, self.layout) }
+    }
+}
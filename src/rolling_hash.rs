@@ -0,0 +1,242 @@
+//! Rolling hash and content-defined chunking (`crypto` feature).
+//!
+//! [`RollingHash`] wraps isa-l_crypto's `rolling_hash2`, which slides a
+//! window of up to 48 bytes over the input and reports the first position
+//! whose hash matches a mask/trigger pair. [`Chunker`] builds on it to split
+//! a buffer at content-defined boundaries, so an insertion early in the data
+//! only changes the chunks around it.
+
+use std::os::raw::{c_int, c_long};
+
+use crate::error::ChunkError;
+use crate::raw::RawState;
+use crate::sys::*;
+
+/// Window used by [`Chunker`].
+pub const DEFAULT_WINDOW: u32 = 32;
+
+/// Generates a hit mask for an expected spacing of about `mean` bytes between
+/// hits; `shift` selects a different set of bits for the same spacing.
+pub fn mask_gen(mean: u32, shift: i32) -> u32 {
+    unsafe { rolling_hashx_mask_gen(mean as c_long, shift as c_int) }
+}
+
+/// `rolling_hash2` state for a fixed window.
+pub struct RollingHash {
+    state: RawState<rh_state2>,
+    window: u32,
+}
+
+// SAFETY: the state holds no pointers outside itself.
+unsafe impl Send for RollingHash {}
+
+impl RollingHash {
+    /// Creates a hash over a `window`-byte window (1 to
+    /// `FINGERPRINT_MAX_WINDOW`).
+    pub fn new(window: u32) -> Result<Self, ChunkError> {
+        if window == 0 {
+            return Err(ChunkError::InvalidWindow(window));
+        }
+        let state = unsafe { RawState::new(isal_rs_rh_state2_size(), isal_rs_rh_state2_align()) };
+        if unsafe { rolling_hash2_init(state.as_ptr(), window) } != 0 {
+            return Err(ChunkError::InvalidWindow(window));
+        }
+        Ok(RollingHash { state, window })
+    }
+
+    /// Window length in bytes.
+    pub fn window(&self) -> u32 {
+        self.window
+    }
+
+    /// Primes the window with the bytes preceding the next [`run`](Self::run).
+    ///
+    /// # Panics
+    /// Panics if `init` is not exactly one window long.
+    pub fn reset(&mut self, init: &[u8]) {
+        assert_eq!(
+            init.len(),
+            self.window as usize,
+            "init must be one window long"
+        );
+        unsafe { rolling_hash2_reset(self.state.as_ptr(), init.as_ptr() as *mut u8) };
+    }
+
+    /// Rolls over `buf` and returns the offset of the first position where
+    /// `hash & mask == trigger`, or `None` if `buf` ends first. The state
+    /// carries over, so consecutive calls continue the same stream.
+    ///
+    /// # Panics
+    /// Panics if `buf` is longer than `u32::MAX` bytes.
+    pub fn run(&mut self, buf: &[u8], mask: u32, trigger: u32) -> Option<usize> {
+        assert!(
+            buf.len() <= u32::MAX as usize,
+            "buffer exceeds u32::MAX bytes"
+        );
+        let mut offset = 0u32;
+        let ret = unsafe {
+            rolling_hash2_run(
+                self.state.as_ptr(),
+                buf.as_ptr() as *mut u8,
+                buf.len() as u32,
+                mask,
+                trigger,
+                &mut offset,
+            )
+        };
+        if ret == FINGERPRINT_RET_HIT {
+            Some(offset as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Iterator over the content-defined chunks of a buffer.
+///
+/// Every chunk except the last is between `min` and `max` bytes long, and
+/// chunk lengths average roughly `avg` on random data. The first `min` bytes
+/// of each chunk are skipped by the hash, so only the tail decides where the
+/// chunk ends.
+pub struct Chunker<'a> {
+    data: &'a [u8],
+    pos: usize,
+    min: usize,
+    max: usize,
+    mask: u32,
+    hash: RollingHash,
+}
+
+impl<'a> Chunker<'a> {
+    /// Chunks `data` with the given size bounds and [`DEFAULT_WINDOW`].
+    ///
+    /// # Errors
+    /// [`ChunkError::InvalidSizes`] unless
+    /// `DEFAULT_WINDOW <= min <= avg <= max <= u32::MAX`.
+    pub fn new(data: &'a [u8], min: usize, avg: usize, max: usize) -> Result<Self, ChunkError> {
+        if min < DEFAULT_WINDOW as usize || min > avg || avg > max || max > u32::MAX as usize {
+            return Err(ChunkError::InvalidSizes { min, avg, max });
+        }
+        // Hits are only looked for past `min`, so aim the spacing at the rest.
+        let mean = (avg - min).max(1) as u32;
+        Ok(Chunker {
+            data,
+            pos: 0,
+            min,
+            max,
+            mask: mask_gen(mean, 0),
+            hash: RollingHash::new(DEFAULT_WINDOW)?,
+        })
+    }
+
+    /// Offset of the next chunk within the input.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a> Iterator for Chunker<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let rest = &self.data[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+
+        let len = if rest.len() <= self.min {
+            rest.len()
+        } else {
+            let window = self.hash.window() as usize;
+            let end = rest.len().min(self.max);
+            self.hash.reset(&rest[self.min - window..self.min]);
+            match self.hash.run(&rest[self.min..end], self.mask, 0) {
+                Some(offset) => self.min + offset,
+                None => end,
+            }
+        };
+
+        self.pos += len;
+        Some(&rest[..len])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(len: usize, mut seed: u64) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (seed >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_bounds() {
+        let data = pseudo_random(1 << 20, 1);
+        let chunks: Vec<&[u8]> = Chunker::new(&data, 2048, 8192, 32768).unwrap().collect();
+
+        assert_eq!(chunks.concat(), data);
+        let (last, body) = chunks.split_last().unwrap();
+        assert!(body.iter().all(|c| (2048..=32768).contains(&c.len())));
+        assert!(!last.is_empty() && last.len() <= 32768);
+
+        let mean = data.len() / chunks.len();
+        assert!((2048..32768).contains(&mean), "mean chunk size {mean}");
+    }
+
+    #[test]
+    fn test_boundaries_resync_after_insert() {
+        let data = pseudo_random(1 << 19, 2);
+        let mut shifted = vec![0xAB; 100];
+        shifted.extend_from_slice(&data);
+
+        let a: Vec<&[u8]> = Chunker::new(&data, 1024, 4096, 16384).unwrap().collect();
+        let b: Vec<&[u8]> = Chunker::new(&shifted, 1024, 4096, 16384).unwrap().collect();
+        let shared = b.iter().filter(|c| a.contains(c)).count();
+        assert!(
+            shared * 10 >= a.len() * 9,
+            "{shared} of {} chunks shared",
+            a.len()
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(matches!(
+            Chunker::new(&[], 16, 64, 128),
+            Err(ChunkError::InvalidSizes { .. })
+        ));
+        assert!(matches!(
+            Chunker::new(&[], 4096, 2048, 8192),
+            Err(ChunkError::InvalidSizes { .. })
+        ));
+        assert!(matches!(
+            RollingHash::new(FINGERPRINT_MAX_WINDOW + 1),
+            Err(ChunkError::InvalidWindow(_))
+        ));
+        assert_eq!(Chunker::new(&[], 64, 128, 256).unwrap().count(), 0);
+        assert_eq!(Chunker::new(&[1; 10], 64, 128, 256).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_run_continues_stream() {
+        let data = pseudo_random(1 << 16, 3);
+        let mask = mask_gen(1024, 0);
+
+        let mut whole = RollingHash::new(DEFAULT_WINDOW).unwrap();
+        whole.reset(&data[..32]);
+        let hit = whole.run(&data[32..], mask, 0).expect("no hit in 64 KiB");
+
+        let mut split = RollingHash::new(DEFAULT_WINDOW).unwrap();
+        split.reset(&data[..32]);
+        let cut = hit / 2;
+        assert_eq!(split.run(&data[32..32 + cut], mask, 0), None);
+        assert_eq!(split.run(&data[32 + cut..], mask, 0), Some(hit - cut));
+    }
+}
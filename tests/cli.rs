@@ -0,0 +1,148 @@
+//! End-to-end checks of the `isal` binary.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn isal(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_isal"))
+        .args(args)
+        .output()
+        .expect("failed to run isal")
+}
+
+/// Fresh scratch directory under the target dir.
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn sample(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i % 251) as u8 ^ (i >> 12) as u8)
+        .collect()
+}
+
+#[test]
+fn test_crc_check_values() {
+    let dir = scratch("cli_crc");
+    let path = dir.join("check");
+    fs::write(&path, b"123456789").unwrap();
+    let path = path.to_str().unwrap();
+
+    for (variant, expected) in [
+        ("crc32_gzip_refl", "cbf43926"),
+        ("crc32_iscsi", "e3069283"),
+        ("crc64_ecma_refl", "995dc9bbdf1939fa"),
+    ] {
+        let out = isal(&["crc", variant, path]);
+        assert!(out.status.success(), "{out:?}");
+        let stdout = String::from_utf8(out.stdout).unwrap();
+        assert_eq!(stdout, format!("{expected}  {path}\n"));
+    }
+    assert_eq!(isal(&["crc", "crc7", path]).status.code(), Some(2));
+}
+
+#[test]
+fn test_gzip_round_trip_threads() {
+    let dir = scratch("cli_gzip");
+    let path = dir.join("data");
+    let data = sample(9 << 20);
+    fs::write(&path, &data).unwrap();
+    let path = path.to_str().unwrap();
+
+    let out = isal(&["gzip", "-1", "-T", "3", path]);
+    assert!(out.status.success(), "{out:?}");
+    fs::remove_file(path).unwrap();
+    let out = isal(&["gunzip", &format!("{path}.gz")]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(fs::read(path).unwrap(), data);
+
+    // Refuses to clobber without -f.
+    assert_eq!(isal(&["gzip", path]).status.code(), Some(1));
+}
+
+#[test]
+fn test_ec_rebuilds_from_k_shards() {
+    let dir = scratch("cli_ec");
+    let path = dir.join("blob");
+    let data = sample(5 << 20 | 123);
+    fs::write(&path, &data).unwrap();
+
+    let out = isal(&["ec", "encode", "-k", "4", "-p", "2", path.to_str().unwrap()]);
+    assert!(out.status.success(), "{out:?}");
+
+    // Lose one shard and corrupt another.
+    fs::remove_file(dir.join("blob.1.shard")).unwrap();
+    let mut parity = fs::read(dir.join("blob.4.shard")).unwrap();
+    parity[10] ^= 1;
+    fs::write(dir.join("blob.4.shard"), parity).unwrap();
+
+    let rebuilt = dir.join("rebuilt");
+    let out = isal(&[
+        "ec",
+        "decode",
+        "-o",
+        rebuilt.to_str().unwrap(),
+        dir.join("blob.manifest").to_str().unwrap(),
+    ]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(fs::read(&rebuilt).unwrap(), data);
+
+    // A third loss is one too many.
+    fs::remove_file(dir.join("blob.0.shard")).unwrap();
+    let out = isal(&[
+        "ec",
+        "decode",
+        "-f",
+        "-o",
+        rebuilt.to_str().unwrap(),
+        dir.join("blob.manifest").to_str().unwrap(),
+    ]);
+    assert_eq!(out.status.code(), Some(1));
+}
+
+#[test]
+fn test_ec_decode_rejects_path_names() {
+    let dir = scratch("cli_ec_names");
+    let path = dir.join("blob");
+    fs::write(&path, sample(1000)).unwrap();
+    let out = isal(&["ec", "encode", "-k", "2", "-p", "1", path.to_str().unwrap()]);
+    assert!(out.status.success(), "{out:?}");
+    let manifest = fs::read_to_string(dir.join("blob.manifest")).unwrap();
+
+    // Without -o the output lands next to the manifest.
+    fs::remove_file(&path).unwrap();
+    let out = isal(&["ec", "decode", dir.join("blob.manifest").to_str().unwrap()]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(fs::read(&path).unwrap(), sample(1000));
+
+    for (from, to) in [
+        ("name=blob", "name=../escaped"),
+        ("name=blob", "name=/tmp/escaped"),
+        ("blob.0.shard", "../blob.0.shard"),
+        ("unit=1048576", "unit=99999999999"),
+        ("unit=1048576", "unit=4096"),
+    ] {
+        let bad = dir.join("bad.manifest");
+        fs::write(&bad, manifest.replacen(from, to, 1)).unwrap();
+        let out = isal(&["ec", "decode", "-f", bad.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(1), "{to}");
+    }
+    assert!(!dir.join("../escaped").exists());
+
+    // A shard of the wrong length is rebuilt, not trusted.
+    let mut shard = fs::read(dir.join("blob.0.shard")).unwrap();
+    shard.extend_from_slice(&[0; 10]);
+    fs::write(dir.join("blob.0.shard"), shard).unwrap();
+    let out = isal(&[
+        "ec",
+        "decode",
+        "-f",
+        dir.join("blob.manifest").to_str().unwrap(),
+    ]);
+    assert!(out.status.success(), "{out:?}");
+    assert_eq!(fs::read(&path).unwrap(), sample(1000));
+}
@@ -0,0 +1,475 @@
+//! Cross-implementation equivalence: every arch-specific kernel the host CPU
+//! supports must produce byte-identical output to the portable `_base`
+//! version for random shapes and data.
+
+use isa_l_rust::sys::*;
+use proptest::prelude::*;
+
+/// Deterministic filler so large buffers don't go through proptest's
+/// per-byte strategies.
+fn fill(len: usize, seed: u64) -> Vec<u8> {
+    let mut x = seed | 1;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as u8
+        })
+        .collect()
+}
+
+macro_rules! crc_pairs {
+    ($data:expr, $init:expr, $($base:ident == $multi:ident),* $(,)?) => {
+        $(
+            let base = unsafe { $base($init as _, $data.as_ptr() as *mut _, $data.len() as u64) };
+            let multi = unsafe { $multi($init as _, $data.as_ptr() as *mut _, $data.len() as u64) };
+            prop_assert_eq!(base, multi, "{}", stringify!($multi));
+        )*
+    };
+}
+
+proptest! {
+    #[test]
+    fn crc_base_matches_dispatched(data in proptest::collection::vec(any::<u8>(), 0..4096), init in any::<u64>()) {
+        crc_pairs!(data, init,
+            crc16_t10dif_base == crc16_t10dif,
+            crc32_ieee_base == crc32_ieee,
+            crc32_gzip_refl_base == crc32_gzip_refl,
+            crc64_ecma_refl_base == crc64_ecma_refl,
+            crc64_ecma_norm_base == crc64_ecma_norm,
+            crc64_iso_refl_base == crc64_iso_refl,
+            crc64_iso_norm_base == crc64_iso_norm,
+            crc64_jones_refl_base == crc64_jones_refl,
+            crc64_jones_norm_base == crc64_jones_norm,
+            crc64_rocksoft_refl_base == crc64_rocksoft_refl,
+            crc64_rocksoft_norm_base == crc64_rocksoft_norm,
+        );
+
+        let ptr = data.as_ptr() as *mut u8;
+        let len = data.len() as std::os::raw::c_int;
+        prop_assert_eq!(
+            unsafe { crc32_iscsi_base(ptr, len, init as u32) },
+            unsafe { crc32_iscsi(ptr, len, init as u32) }
+        );
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use std::os::raw::{c_int, c_void};
+
+    use isa_l_rust::cpu::{cpu_features, Backend};
+    use isa_l_rust::sys::*;
+    use proptest::prelude::*;
+
+    use super::fill;
+
+    type EncodeFn = unsafe extern "C" fn(c_int, c_int, c_int, *mut u8, *mut *mut u8, *mut *mut u8);
+    type DotFn = unsafe extern "C" fn(c_int, c_int, *mut u8, *mut *mut u8, *mut u8);
+    type DotNFn = unsafe extern "C" fn(c_int, c_int, *mut u8, *mut *mut u8, *mut *mut u8);
+    type MadFn = unsafe extern "C" fn(c_int, c_int, c_int, *mut u8, *mut u8, *mut u8);
+    type MadNFn = unsafe extern "C" fn(c_int, c_int, c_int, *mut u8, *mut u8, *mut *mut u8);
+    type RaidFn = unsafe extern "C" fn(c_int, c_int, *mut *mut c_void) -> c_int;
+
+    type Kernel<F> = (Backend, F, &'static str);
+
+    /// One kernel per `Backend` other than `Base`, in `Backend::ALL` order
+    /// minus the first entry.
+    macro_rules! per_backend {
+        ($sse:ident, $avx:ident, $avx2:ident, $avx2_gfni:ident, $avx512:ident, $avx512_gfni:ident) => {
+            [
+                (Backend::Sse, $sse as _, stringify!($sse)),
+                (Backend::Avx, $avx as _, stringify!($avx)),
+                (Backend::Avx2, $avx2 as _, stringify!($avx2)),
+                (Backend::Avx2Gfni, $avx2_gfni as _, stringify!($avx2_gfni)),
+                (Backend::Avx512, $avx512 as _, stringify!($avx512)),
+                (
+                    Backend::Avx512Gfni,
+                    $avx512_gfni as _,
+                    stringify!($avx512_gfni),
+                ),
+            ]
+        };
+    }
+
+    const EC_ENCODE: [Kernel<EncodeFn>; 6] = per_backend!(
+        ec_encode_data_sse,
+        ec_encode_data_avx,
+        ec_encode_data_avx2,
+        ec_encode_data_avx2_gfni,
+        ec_encode_data_avx512,
+        ec_encode_data_avx512_gfni
+    );
+
+    const DOT_PROD: [Kernel<DotFn>; 6] = per_backend!(
+        gf_vect_dot_prod_sse,
+        gf_vect_dot_prod_avx,
+        gf_vect_dot_prod_avx2,
+        gf_vect_dot_prod_avx2_gfni,
+        gf_vect_dot_prod_avx512,
+        gf_vect_dot_prod_avx512_gfni
+    );
+
+    const DOT_PROD_N: [(usize, [Kernel<DotNFn>; 6]); 5] = [
+        (
+            2,
+            per_backend!(
+                gf_2vect_dot_prod_sse,
+                gf_2vect_dot_prod_avx,
+                gf_2vect_dot_prod_avx2,
+                gf_2vect_dot_prod_avx2_gfni,
+                gf_2vect_dot_prod_avx512,
+                gf_2vect_dot_prod_avx512_gfni
+            ),
+        ),
+        (
+            3,
+            per_backend!(
+                gf_3vect_dot_prod_sse,
+                gf_3vect_dot_prod_avx,
+                gf_3vect_dot_prod_avx2,
+                gf_3vect_dot_prod_avx2_gfni,
+                gf_3vect_dot_prod_avx512,
+                gf_3vect_dot_prod_avx512_gfni
+            ),
+        ),
+        (
+            4,
+            per_backend!(
+                gf_4vect_dot_prod_sse,
+                gf_4vect_dot_prod_avx,
+                gf_4vect_dot_prod_avx2,
+                gf_4vect_dot_prod_avx2_gfni,
+                gf_4vect_dot_prod_avx512,
+                gf_4vect_dot_prod_avx512_gfni
+            ),
+        ),
+        (
+            5,
+            per_backend!(
+                gf_5vect_dot_prod_sse,
+                gf_5vect_dot_prod_avx,
+                gf_5vect_dot_prod_avx2,
+                gf_5vect_dot_prod_avx2_gfni,
+                gf_5vect_dot_prod_avx512,
+                gf_5vect_dot_prod_avx512_gfni
+            ),
+        ),
+        (
+            6,
+            per_backend!(
+                gf_6vect_dot_prod_sse,
+                gf_6vect_dot_prod_avx,
+                gf_6vect_dot_prod_avx2,
+                gf_6vect_dot_prod_avx2_gfni,
+                gf_6vect_dot_prod_avx512,
+                gf_6vect_dot_prod_avx512_gfni
+            ),
+        ),
+    ];
+
+    const MAD: [Kernel<MadFn>; 6] = per_backend!(
+        gf_vect_mad_sse,
+        gf_vect_mad_avx,
+        gf_vect_mad_avx2,
+        gf_vect_mad_avx2_gfni,
+        gf_vect_mad_avx512,
+        gf_vect_mad_avx512_gfni
+    );
+
+    const MAD_N: [(usize, [Kernel<MadNFn>; 6]); 5] = [
+        (
+            2,
+            per_backend!(
+                gf_2vect_mad_sse,
+                gf_2vect_mad_avx,
+                gf_2vect_mad_avx2,
+                gf_2vect_mad_avx2_gfni,
+                gf_2vect_mad_avx512,
+                gf_2vect_mad_avx512_gfni
+            ),
+        ),
+        (
+            3,
+            per_backend!(
+                gf_3vect_mad_sse,
+                gf_3vect_mad_avx,
+                gf_3vect_mad_avx2,
+                gf_3vect_mad_avx2_gfni,
+                gf_3vect_mad_avx512,
+                gf_3vect_mad_avx512_gfni
+            ),
+        ),
+        (
+            4,
+            per_backend!(
+                gf_4vect_mad_sse,
+                gf_4vect_mad_avx,
+                gf_4vect_mad_avx2,
+                gf_4vect_mad_avx2_gfni,
+                gf_4vect_mad_avx512,
+                gf_4vect_mad_avx512_gfni
+            ),
+        ),
+        (
+            5,
+            per_backend!(
+                gf_5vect_mad_sse,
+                gf_5vect_mad_avx,
+                gf_5vect_mad_avx2,
+                gf_5vect_mad_avx2_gfni,
+                gf_5vect_mad_avx512,
+                gf_5vect_mad_avx512_gfni
+            ),
+        ),
+        (
+            6,
+            per_backend!(
+                gf_6vect_mad_sse,
+                gf_6vect_mad_avx,
+                gf_6vect_mad_avx2,
+                gf_6vect_mad_avx2_gfni,
+                gf_6vect_mad_avx512,
+                gf_6vect_mad_avx512_gfni
+            ),
+        ),
+    ];
+
+    const XOR_GEN: [Kernel<RaidFn>; 3] = [
+        (Backend::Sse, xor_gen_sse, "xor_gen_sse"),
+        (Backend::Avx, xor_gen_avx, "xor_gen_avx"),
+        (Backend::Avx512, xor_gen_avx512, "xor_gen_avx512"),
+    ];
+
+    const PQ_GEN: [Kernel<RaidFn>; 4] = [
+        (Backend::Sse, pq_gen_sse, "pq_gen_sse"),
+        (Backend::Avx, pq_gen_avx, "pq_gen_avx"),
+        (Backend::Avx2, pq_gen_avx2, "pq_gen_avx2"),
+        (Backend::Avx512, pq_gen_avx512, "pq_gen_avx512"),
+    ];
+
+    /// Tables for a `rows x k` coefficient matrix in the format `backend`
+    /// consumes, plus the per-row stride.
+    fn tables(backend: Backend, k: usize, rows: usize, coefs: &[u8]) -> (Vec<u8>, usize) {
+        let per_coef = if backend.uses_gfni_tables() { 8 } else { 32 };
+        let mut t = vec![0u8; k * rows * per_coef];
+        let a = coefs.as_ptr() as *mut u8;
+        unsafe {
+            if backend.uses_gfni_tables() {
+                ec_init_tables_gfni(k as c_int, rows as c_int, a, t.as_mut_ptr());
+            } else {
+                ec_init_tables_base(k as c_int, rows as c_int, a, t.as_mut_ptr());
+            }
+        }
+        (t, k * per_coef)
+    }
+
+    fn ptrs(bufs: &mut [Vec<u8>]) -> Vec<*mut u8> {
+        bufs.iter_mut().map(|b| b.as_mut_ptr()).collect()
+    }
+
+    /// Shape of one case: `k` sources, `rows` outputs, `len` bytes each. The
+    /// SIMD GF kernels need at least 64 bytes.
+    fn shape() -> impl Strategy<Value = (usize, usize, usize, u64)> {
+        (1usize..=20, 1usize..=8, 64usize..2048, any::<u64>())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn ec_encode_data_variants((k, rows, len, seed) in shape()) {
+            let coefs = fill(k * rows, seed);
+            let mut src: Vec<Vec<u8>> = (0..k).map(|i| fill(len, seed ^ i as u64)).collect();
+            let mut expected = vec![vec![0u8; len]; rows];
+            let (base_tbl, _) = tables(Backend::Base, k, rows, &coefs);
+            unsafe {
+                ec_encode_data_base(
+                    len as c_int, k as c_int, rows as c_int, base_tbl.as_ptr() as *mut u8,
+                    ptrs(&mut src).as_mut_ptr(), ptrs(&mut expected).as_mut_ptr(),
+                );
+            }
+
+            for (backend, f, name) in EC_ENCODE {
+                if !backend.is_supported() {
+                    continue;
+                }
+                let (tbl, _) = tables(backend, k, rows, &coefs);
+                let mut out = vec![vec![0u8; len]; rows];
+                unsafe {
+                    f(
+                        len as c_int, k as c_int, rows as c_int, tbl.as_ptr() as *mut u8,
+                        ptrs(&mut src).as_mut_ptr(), ptrs(&mut out).as_mut_ptr(),
+                    );
+                }
+                prop_assert_eq!(&out, &expected, "{}", name);
+            }
+        }
+
+        #[test]
+        fn gf_dot_prod_variants((k, _rows, len, seed) in shape()) {
+            let coefs = fill(6 * k, seed);
+            let mut src: Vec<Vec<u8>> = (0..k).map(|i| fill(len, seed ^ i as u64)).collect();
+
+            // Reference: one gf_vect_dot_prod_base per output row.
+            let (base_tbl, stride) = tables(Backend::Base, k, 6, &coefs);
+            let mut expected = vec![vec![0u8; len]; 6];
+            for (r, dest) in expected.iter_mut().enumerate() {
+                unsafe {
+                    gf_vect_dot_prod_base(
+                        len as c_int, k as c_int, base_tbl[r * stride..].as_ptr() as *mut u8,
+                        ptrs(&mut src).as_mut_ptr(), dest.as_mut_ptr(),
+                    );
+                }
+            }
+
+            for (backend, f, name) in DOT_PROD {
+                if !backend.is_supported() {
+                    continue;
+                }
+                let (tbl, _) = tables(backend, k, 1, &coefs[..k]);
+                let mut out = vec![0u8; len];
+                unsafe {
+                    f(len as c_int, k as c_int, tbl.as_ptr() as *mut u8,
+                      ptrs(&mut src).as_mut_ptr(), out.as_mut_ptr());
+                }
+                prop_assert_eq!(&out, &expected[0], "{}", name);
+            }
+
+            for (n, kernels) in DOT_PROD_N {
+                for (backend, f, name) in kernels {
+                    if !backend.is_supported() {
+                        continue;
+                    }
+                    let (tbl, _) = tables(backend, k, n, &coefs[..n * k]);
+                    let mut out = vec![vec![0u8; len]; n];
+                    unsafe {
+                        f(len as c_int, k as c_int, tbl.as_ptr() as *mut u8,
+                          ptrs(&mut src).as_mut_ptr(), ptrs(&mut out).as_mut_ptr());
+                    }
+                    prop_assert_eq!(&out[..], &expected[..n], "{}", name);
+                }
+            }
+        }
+
+        #[test]
+        fn gf_mad_variants((k, _rows, len, seed) in shape(), vec_i in any::<prop::sample::Index>()) {
+            let vec_i = vec_i.index(k);
+            let coefs = fill(6 * k, seed);
+            let src = fill(len, !seed);
+            let initial: Vec<Vec<u8>> = (0..6).map(|r| fill(len, seed ^ (r << 32))).collect();
+
+            // Reference: one gf_vect_mad_base per destination row.
+            let (base_tbl, stride) = tables(Backend::Base, k, 6, &coefs);
+            let mut expected = initial.clone();
+            for (r, dest) in expected.iter_mut().enumerate() {
+                unsafe {
+                    gf_vect_mad_base(
+                        len as c_int, k as c_int, vec_i as c_int,
+                        base_tbl[r * stride..].as_ptr() as *mut u8,
+                        src.as_ptr() as *mut u8, dest.as_mut_ptr(),
+                    );
+                }
+            }
+
+            for (backend, f, name) in MAD {
+                if !backend.is_supported() {
+                    continue;
+                }
+                let (tbl, _) = tables(backend, k, 1, &coefs[..k]);
+                let mut out = initial[0].clone();
+                unsafe {
+                    f(len as c_int, k as c_int, vec_i as c_int, tbl.as_ptr() as *mut u8,
+                      src.as_ptr() as *mut u8, out.as_mut_ptr());
+                }
+                prop_assert_eq!(&out, &expected[0], "{}", name);
+            }
+
+            for (n, kernels) in MAD_N {
+                for (backend, f, name) in kernels {
+                    if !backend.is_supported() {
+                        continue;
+                    }
+                    let (tbl, _) = tables(backend, k, n, &coefs[..n * k]);
+                    let mut out = initial[..n].to_vec();
+                    unsafe {
+                        f(len as c_int, k as c_int, vec_i as c_int, tbl.as_ptr() as *mut u8,
+                          src.as_ptr() as *mut u8, ptrs(&mut out).as_mut_ptr());
+                    }
+                    prop_assert_eq!(&out[..], &expected[..n], "{}", name);
+                }
+            }
+        }
+
+        #[test]
+        fn raid_variants(sources in 2usize..=12, blocks in 1usize..=32, seed in any::<u64>()) {
+            // The SIMD RAID kernels want 64-byte aligned vectors whose length
+            // is a multiple of 64.
+            let len = blocks * 64;
+            let mut arena = vec![0u8; (sources + 2) * len + 64];
+            let off = arena.as_ptr().align_offset(64);
+            let data = fill(sources * len, seed);
+            arena[off..off + sources * len].copy_from_slice(&data);
+            let base = unsafe { arena.as_mut_ptr().add(off) };
+            let vectors = |n: usize| -> Vec<*mut c_void> {
+                (0..n).map(|i| unsafe { base.add(i * len) } as *mut c_void).collect()
+            };
+            let parity = |arena: &Vec<u8>, n: usize| -> Vec<u8> {
+                arena[off + sources * len..off + (sources + n) * len].to_vec()
+            };
+
+            unsafe { xor_gen_base((sources + 1) as c_int, len as c_int, vectors(sources + 1).as_mut_ptr()) };
+            let xor_expected = parity(&arena, 1);
+            for (backend, f, name) in XOR_GEN {
+                if !backend.is_supported() {
+                    continue;
+                }
+                arena[off + sources * len..].iter_mut().for_each(|b| *b = 0);
+                let ret = unsafe { f((sources + 1) as c_int, len as c_int, vectors(sources + 1).as_mut_ptr()) };
+                prop_assert_eq!(ret, 0, "{}", name);
+                prop_assert_eq!(&parity(&arena, 1), &xor_expected, "{}", name);
+            }
+
+            unsafe { pq_gen_base((sources + 2) as c_int, len as c_int, vectors(sources + 2).as_mut_ptr()) };
+            let pq_expected = parity(&arena, 2);
+            for (backend, f, name) in PQ_GEN {
+                if !backend.is_supported() {
+                    continue;
+                }
+                arena[off + sources * len..].iter_mut().for_each(|b| *b = 0);
+                let ret = unsafe { f((sources + 2) as c_int, len as c_int, vectors(sources + 2).as_mut_ptr()) };
+                prop_assert_eq!(ret, 0, "{}", name);
+                prop_assert_eq!(&parity(&arena, 2), &pq_expected, "{}", name);
+            }
+        }
+
+        #[test]
+        fn crc64_by8_variants(data in proptest::collection::vec(any::<u8>(), 0..4096), init in any::<u64>()) {
+            let cpu = cpu_features();
+            prop_assume!(cpu.clmul && cpu.sse4_1);
+            macro_rules! by8 {
+                ($($base:ident == $fast:ident),* $(,)?) => {
+                    $(
+                        prop_assert_eq!(
+                            unsafe { $base(init, data.as_ptr(), data.len() as u64) },
+                            unsafe { $fast(init, data.as_ptr(), data.len() as u64) },
+                            "{}",
+                            stringify!($fast)
+                        );
+                    )*
+                };
+            }
+            by8!(
+                crc64_ecma_refl_base == crc64_ecma_refl_by8,
+                crc64_ecma_norm_base == crc64_ecma_norm_by8,
+                crc64_iso_refl_base == crc64_iso_refl_by8,
+                crc64_iso_norm_base == crc64_iso_norm_by8,
+                crc64_jones_refl_base == crc64_jones_refl_by8,
+                crc64_jones_norm_base == crc64_jones_norm_by8,
+                crc64_rocksoft_refl_base == crc64_rocksoft_refl_by8,
+                crc64_rocksoft_norm_base == crc64_rocksoft_norm_by8,
+            );
+        }
+    }
+}